@@ -0,0 +1,97 @@
+// PS2 VAGp (PSX ADPCM) decoding.
+// A VAGp stream is a 48 byte big endian header followed by 16 byte blocks,
+// each holding 28 4-bit samples.
+
+const VAG_HEADER_SIZE: usize = 0x30;
+const VAG_BLOCK_SIZE: usize = 0x10;
+const VAG_SIGNATURE: &[u8; 4] = b"VAGp";
+const VAG_FLAG_END: u8 = 0x07;
+
+// Prediction filter coefficients, scaled by 64
+const VAG_FILTERS: [[i32; 2]; 5] = [
+	[0, 0],
+	[60, 0],
+	[115, -52],
+	[98, -55],
+	[122, -60],
+];
+
+
+// Decodes a VAGp stream into 16-bit mono PCM.
+// Returns (sample rate, samples), or None if the data is not a VAGp stream.
+pub fn decode_vag(vag_data: &[u8]) -> Option<(u32, Vec<i16>)> {
+	if vag_data.len() < VAG_HEADER_SIZE || &vag_data[0x00..0x04] != VAG_SIGNATURE {
+		return None;
+	}
+	
+	let data_size: usize = u32::from_be_bytes([
+		vag_data[0x0C], vag_data[0x0D], vag_data[0x0E], vag_data[0x0F]
+	]) as usize;
+	
+	let sample_rate: u32 = u32::from_be_bytes([
+		vag_data[0x10], vag_data[0x11], vag_data[0x12], vag_data[0x13]
+	]);
+	
+	// Trust the header only as far as the data goes
+	let data_end: usize = std::cmp::min(VAG_HEADER_SIZE + data_size, vag_data.len());
+	let mut samples: Vec<i16> = Vec::with_capacity((data_end - VAG_HEADER_SIZE) / VAG_BLOCK_SIZE * 28);
+	let mut history: [i32; 2] = [0, 0];
+	
+	for block in vag_data[VAG_HEADER_SIZE..data_end].chunks_exact(VAG_BLOCK_SIZE) {
+		let shift: u32 = (block[0] & 0x0F) as u32;
+		let filter: [i32; 2] = VAG_FILTERS.get((block[0] >> 4) as usize).copied().unwrap_or([0, 0]);
+		let flags: u8 = block[1];
+		
+		if flags == VAG_FLAG_END {
+			break;
+		}
+		
+		for byte in &block[2..] {
+			// Low nibble first
+			for nibble in [byte & 0x0F, byte >> 4] {
+				// Sign extend the nibble into the top of a 16-bit sample
+				let mut sample: i32 = (((nibble as u16) << 12) as i16 as i32) >> shift;
+				sample += (history[0] * filter[0] + history[1] * filter[1] + 32) >> 6;
+				sample = sample.clamp(i16::MIN as i32, i16::MAX as i32);
+				
+				history[1] = history[0];
+				history[0] = sample;
+				samples.push(sample as i16);
+			}
+		}
+	}
+	
+	return Some((sample_rate, samples));
+}
+
+
+// Wraps 16-bit mono PCM samples in a WAV file.
+pub fn make_wav(sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+	let data_size: u32 = (samples.len() * 2) as u32;
+	let mut wav_data: Vec<u8> = Vec::with_capacity(44 + data_size as usize);
+	
+	// RIFF header
+	wav_data.extend_from_slice(b"RIFF");
+	wav_data.extend_from_slice(&(36 + data_size).to_le_bytes());
+	wav_data.extend_from_slice(b"WAVE");
+	
+	// Format chunk: PCM, mono, 16-bit
+	wav_data.extend_from_slice(b"fmt ");
+	wav_data.extend_from_slice(&16u32.to_le_bytes());
+	wav_data.extend_from_slice(&1u16.to_le_bytes());
+	wav_data.extend_from_slice(&1u16.to_le_bytes());
+	wav_data.extend_from_slice(&sample_rate.to_le_bytes());
+	wav_data.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+	wav_data.extend_from_slice(&2u16.to_le_bytes());
+	wav_data.extend_from_slice(&16u16.to_le_bytes());
+	
+	// Data chunk
+	wav_data.extend_from_slice(b"data");
+	wav_data.extend_from_slice(&data_size.to_le_bytes());
+	
+	for sample in samples {
+		wav_data.extend_from_slice(&sample.to_le_bytes());
+	}
+	
+	return wav_data;
+}
\ No newline at end of file
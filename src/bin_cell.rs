@@ -7,9 +7,20 @@ use serde::Serialize;
 use serde::Deserialize;
 
 use godot::prelude::*;
+use godot::classes::Image;
+use godot::classes::ImageTexture;
+use godot::classes::image::Format;
 
+use crate::bin_sprite::BinSprite;
+use crate::sprite_transform;
+use crate::logging::*;
 
-#[derive(Serialize, Deserialize, Debug)]
+
+// No cell in the game comes close; anything above this is corrupt data.
+pub const MAX_HITBOX_COUNT: u32 = 0x400;
+
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 struct CellJSONBox {
 	x_offset: i16,
 	y_offset: i16,
@@ -19,16 +30,19 @@ struct CellJSONBox {
 }
 
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 struct CellJSONSpriteInfo {
 	index: u16,
 	unk: u32,
 	x_offset: i16,
 	y_offset: i16,
+	// Missing from older cell files
+	#[serde(default)]
+	unk_2: u16,
 }
 
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 struct CellJSON {
 	boxes: Vec<CellJSONBox>,
 	sprite_info: CellJSONSpriteInfo,
@@ -141,36 +155,33 @@ impl Cell {
 	
 	// Returns a binary representation of the cell.
 	pub fn to_bin(&self) -> Vec<u8> {
-		let mut bin_data: Vec<u8> = Vec::new();
+		return cell_json_to_bin(&self.to_cell_json());
+	}
+	
+	
+	/// Cell array constructor from a single .json file holding an array of cells.
+	pub fn from_array_file(path_buf: PathBuf) -> Option<Vec<Gd<Self>>> {
+		let string: String;
 		
-		// Hitbox count
-		bin_data.extend((self.boxes.len() as u32).to_le_bytes());
+		match fs::read_to_string(&path_buf) {
+			Ok(value) => string = value,
+			_ => return None,
+		}
 		
-		// Hitbox data
-		for hitbox in self.boxes.iter_shared() {
-			let binding = hitbox.bind();
-			bin_data.extend(binding.x_offset.to_le_bytes());
-			bin_data.extend(binding.y_offset.to_le_bytes());
-			bin_data.extend(binding.width.to_le_bytes());
-			bin_data.extend(binding.height.to_le_bytes());
-			bin_data.extend(binding.box_type.to_le_bytes());
-			bin_data.push(binding.crop_x_offset);
-			bin_data.push(binding.crop_y_offset);
+		let cell_json_vector: Vec<CellJSON>;
+		
+		match parse_cell_array(&string) {
+			Some(cells) => cell_json_vector = cells,
+			None => return None,
 		}
 		
-		// Rest of data
-		bin_data.extend((self.sprite_x_offset).to_le_bytes());
-		bin_data.extend((self.sprite_y_offset).to_le_bytes());
-		bin_data.extend((self.unknown_1).to_le_bytes());
-		bin_data.extend((self.sprite_index).to_le_bytes());
-		bin_data.extend((self.unknown_2).to_le_bytes());
+		let mut cells: Vec<Gd<Self>> = Vec::new();
 		
-		// Pad and align
-		while bin_data.len() % 0x10 != 0x00 {
-			bin_data.push(0xFF);
+		for cell_json in cell_json_vector {
+			cells.push(Self::from_cell_json(cell_json));
 		}
 		
-		return bin_data;
+		return Some(cells);
 	}
 	
 	
@@ -178,11 +189,16 @@ impl Cell {
 	pub fn from_json_string(string: String) -> Option<Gd<Self>> {
 		let cell_json: CellJSON;
 
-		match serde_json::from_str(&string) {
-			Ok(cell) => cell_json = cell,
-			_ => return None,
+		match parse_cell(&string) {
+			Some(cell) => cell_json = cell,
+			None => return None,
 		}
 		
+		return Some(Self::from_cell_json(cell_json));
+	}
+	
+	
+	fn from_cell_json(cell_json: CellJSON) -> Gd<Self> {
 		// Get hitboxes
 		let mut hitbox_array: Array<Gd<BoxInfo>> = Array::new();
 		for hitbox in cell_json.boxes.iter() {
@@ -212,93 +228,36 @@ impl Cell {
 					sprite_y_offset: cell_json.sprite_info.y_offset,
 					unknown_1: cell_json.sprite_info.unk,
 					sprite_index: cell_json.sprite_info.index,
-					unknown_2: 0,
+					unknown_2: cell_json.sprite_info.unk_2,
 				}
 			}
 		);
 		
-		return Some(cell);
+		return cell;
 	}
 
 
 	// Cell constructor from raw binary data.
 	pub fn from_binary_data(bin_data: &[u8]) -> Option<Gd<Self>> {
-		if bin_data.len() < 0x04 {
-			return None;
-		}
-	
-		let hitbox_count: u32 = u32::from_le_bytes(
-			[bin_data[0x00], bin_data[0x01], bin_data[0x02], bin_data[0x03]]
-		);
-		
-		if bin_data.len() < (0x0C * hitbox_count as usize) + 0x10 {
-			return None;
-		}
-		
-		let mut hitbox_array: Array<Gd<BoxInfo>> = Array::new();
-		for hitbox_number in 0..hitbox_count as usize {
-			hitbox_array.push(&Gd::from_init_fn(|base| {
-				BoxInfo {
-					base: base,
-					x_offset: i16::from_le_bytes([
-						bin_data[0x0C * hitbox_number + 0x04],
-						bin_data[0x0C * hitbox_number + 0x05]
-					]),
-					y_offset: i16::from_le_bytes([
-						bin_data[0x0C * hitbox_number + 0x06],
-						bin_data[0x0C * hitbox_number + 0x07]
-					]),
-					width: u16::from_le_bytes([
-						bin_data[0x0C * hitbox_number + 0x08],
-						bin_data[0x0C * hitbox_number + 0x09]
-					]),
-					height: u16::from_le_bytes([
-						bin_data[0x0C * hitbox_number + 0x0A],
-						bin_data[0x0C * hitbox_number + 0x0B]
-					]),
-					box_type: u16::from_le_bytes([
-						bin_data[0x0C * hitbox_number + 0x0C],
-						bin_data[0x0C * hitbox_number + 0x0D]
-					]),
-					crop_x_offset: bin_data[0x0C * hitbox_number + 0x0E],
-					crop_y_offset: bin_data[0x0C * hitbox_number + 0x0F],
-				}
-			}));
+		match cell_json_from_bin(bin_data) {
+			Some(cell_json) => return Some(Self::from_cell_json(cell_json)),
+			None => return None,
 		}
-		
-		let cursor: usize = (hitbox_count as usize) * 0x0C + 0x04;
-		return Some(Gd::from_init_fn(|base| {
-		Cell {
-				base,
-				boxes: hitbox_array,
-				sprite_x_offset: i16::from_le_bytes([
-					bin_data[cursor + 0x00], bin_data[cursor + 0x01]
-				]),
-				sprite_y_offset: i16::from_le_bytes([
-					bin_data[cursor + 0x02], bin_data[cursor + 0x03]
-				]),
-				unknown_1: u32::from_le_bytes([
-					bin_data[cursor + 0x04], bin_data[cursor + 0x05],
-					bin_data[cursor + 0x06], bin_data[cursor + 0x07]
-				]),
-				sprite_index: u16::from_le_bytes([
-					bin_data[cursor + 0x08], bin_data[cursor + 0x09]
-				]),
-				unknown_2: u16::from_le_bytes([
-					bin_data[cursor + 0x0A], bin_data[cursor + 0x0B]
-				]),
-			}
-		}));
 	}
 
 
 	/// Serializes the cell into a JSON string.
 	pub fn serialize(&self) -> String {
+		return serde_json::to_string_pretty(&self.to_cell_json()).unwrap();
+	}
+	
+	
+	fn to_cell_json(&self) -> CellJSON {
 		let mut boxes: Vec<CellJSONBox> = Vec::new();
 		
-		for mut hitbox in self.boxes.iter_shared() {
-			let box_mut = hitbox.bind_mut();
-			let this_box: &BoxInfo = box_mut.deref();
+		for hitbox in self.boxes.iter_shared() {
+			let binding = hitbox.bind();
+			let this_box: &BoxInfo = binding.deref();
 			
 			let json_box: CellJSONBox = CellJSONBox {
 				x_offset: this_box.x_offset,
@@ -316,14 +275,358 @@ impl Cell {
 			unk: self.unknown_1,
 			x_offset: self.sprite_x_offset,
 			y_offset: self.sprite_y_offset,
+			unk_2: self.unknown_2,
 		};
 		
-		let cell_json = CellJSON {
+		return CellJSON {
 			boxes,
 			sprite_info,
 		};
+	}
+	
+	
+	/// Returns this cell's boxes as plain dictionaries, with the same keys as BoxInfo's properties.
+	#[func] pub fn boxes_as_dicts(&self) -> Array<Dictionary> {
+		let mut box_dicts: Array<Dictionary> = Array::new();
+		
+		for hitbox in self.boxes.iter_shared() {
+			let binding = hitbox.bind();
+			
+			box_dicts.push(&dict! {
+				"x_offset": binding.x_offset,
+				"y_offset": binding.y_offset,
+				"width": binding.width,
+				"height": binding.height,
+				"box_type": binding.box_type,
+				"crop_x_offset": binding.crop_x_offset,
+				"crop_y_offset": binding.crop_y_offset,
+			});
+		}
+		
+		return box_dicts;
+	}
+	
+	
+	/// Replaces this cell's boxes with ones built from dictionaries like those from boxes_as_dicts().
+	/// Missing keys default to 0; values are truncated to each property's size.
+	#[func] pub fn set_boxes_from_dicts(&mut self, box_dicts: Array<Dictionary>) {
+		let mut hitbox_array: Array<Gd<BoxInfo>> = Array::new();
+		
+		for box_dict in box_dicts.iter_shared() {
+			let value = |key: &str| -> i64 {
+				return box_dict.get(key).map_or(0, |value| value.try_to::<i64>().unwrap_or(0));
+			};
+			
+			hitbox_array.push(&Gd::from_init_fn(|base| {
+				BoxInfo {
+					base: base,
+					x_offset: value("x_offset") as i16,
+					y_offset: value("y_offset") as i16,
+					width: value("width") as u16,
+					height: value("height") as u16,
+					box_type: value("box_type") as u16,
+					crop_x_offset: value("crop_x_offset") as u8,
+					crop_y_offset: value("crop_y_offset") as u8,
+				}
+			}));
+		}
+		
+		self.boxes = hitbox_array;
+	}
+	
+	
+	/// Builds a per-type display array of `length` entries, true for each type in `types`.
+	/// Types outside the array's range are ignored.
+	#[func] pub fn display_array_from_types(types: PackedInt64Array, length: i64) -> Array<bool> {
+		let mut display: Vec<bool> = vec![false; std::cmp::max(length, 0) as usize];
+		
+		for box_type in types.as_slice() {
+			if *box_type >= 0 && (*box_type as usize) < display.len() {
+				display[*box_type as usize] = true;
+			}
+		}
+		
+		return display.into_iter().collect();
+	}
+	
+	
+	/// Returns the types set to true in a per-type display array, in ascending order.
+	#[func] pub fn types_from_display_array(display: Array<bool>) -> PackedInt64Array {
+		let mut types: Vec<i64> = Vec::new();
+		
+		for (box_type, shown) in display.iter_shared().enumerate() {
+			if shown {
+				types.push(box_type as i64);
+			}
+		}
+		
+		return PackedInt64Array::from(types);
+	}
+	
+	
+	/// Returns the pixel extents of this cell relative to the object's origin: the sprite at
+	/// its offsets, every box whose type is true in `box_display_types`, and the origin
+	/// pixel itself if `origin` (the origin marker's color) is not empty.
+	/// Returns an empty rect if nothing is visible.
+	#[func] pub fn get_bounds(
+		&self, sprite: Gd<BinSprite>, box_display_types: Array<bool>, origin: PackedByteArray
+	) -> Rect2i {
+		let mut left: i32 = i32::MAX;
+		let mut top: i32 = i32::MAX;
+		let mut right: i32 = i32::MIN;
+		let mut bottom: i32 = i32::MIN;
+		
+		let mut include = |x: i32, y: i32, width: i32, height: i32| {
+			if width <= 0 || height <= 0 {
+				return;
+			}
+			
+			left = std::cmp::min(left, x);
+			top = std::cmp::min(top, y);
+			right = std::cmp::max(right, x + width);
+			bottom = std::cmp::max(bottom, y + height);
+		};
+		
+		match &sprite.bind().image {
+			Some(image) => include(
+				self.sprite_x_offset as i32, self.sprite_y_offset as i32, image.get_width(), image.get_height()
+			),
+			
+			None => (),
+		}
+		
+		for hitbox in self.boxes.iter_shared() {
+			let binding = hitbox.bind();
+			
+			if !box_display_types.get(binding.box_type as usize).unwrap_or(false) {
+				continue;
+			}
+			
+			include(
+				binding.x_offset as i32, binding.y_offset as i32, binding.width as i32, binding.height as i32
+			);
+		}
+		
+		if !origin.is_empty() {
+			include(0, 0, 1, 1);
+		}
+		
+		if left > right || top > bottom {
+			return Rect2i::default();
+		}
 		
-		return serde_json::to_string_pretty(&cell_json).unwrap();
+		return Rect2i::new(Vector2i::new(left, top), Vector2i::new(right - left, bottom - top));
+	}
+	
+	
+	/// Returns (attacker box index, defender box index) for every attacker hitbox (type 0/1)
+	/// that overlaps a defender hurtbox (type 2). Each cell is mirrored about its origin if
+	/// its flip flag is set, then moved by its offset. Boxes that only touch don't overlap.
+	#[func] pub fn boxes_intersect(
+		attacker: Gd<Cell>,
+		defender: Gd<Cell>,
+		attacker_flip: bool,
+		defender_flip: bool,
+		attacker_offset: Vector2i,
+		defender_offset: Vector2i,
+	) -> Array<Vector2i> {
+		let hitboxes: Vec<(usize, Rect2i)> = attacker.bind().world_boxes(&[0, 1], attacker_flip, attacker_offset);
+		let hurtboxes: Vec<(usize, Rect2i)> = defender.bind().world_boxes(&[2], defender_flip, defender_offset);
+		let mut pairs: Array<Vector2i> = Array::new();
+		
+		for (hit_index, hit_rect) in hitboxes.iter() {
+			for (hurt_index, hurt_rect) in hurtboxes.iter() {
+				if hit_rect.intersects(*hurt_rect) {
+					pairs.push(Vector2i::new(*hit_index as i32, *hurt_index as i32));
+				}
+			}
+		}
+		
+		return pairs;
+	}
+	
+	
+	// Returns (index, rect) for boxes of the given types, placed in world space.
+	fn world_boxes(&self, box_types: &[u16], flip: bool, offset: Vector2i) -> Vec<(usize, Rect2i)> {
+		let mut rects: Vec<(usize, Rect2i)> = Vec::new();
+		
+		for (index, hitbox) in self.boxes.iter_shared().enumerate() {
+			let binding = hitbox.bind();
+			
+			if !box_types.contains(&binding.box_type) || binding.width == 0 || binding.height == 0 {
+				continue;
+			}
+			
+			let mut x: i32 = binding.x_offset as i32;
+			
+			if flip {
+				x = -(x + binding.width as i32);
+			}
+			
+			rects.push((index, Rect2i::new(
+				Vector2i::new(x + offset.x, binding.y_offset as i32 + offset.y),
+				Vector2i::new(binding.width as i32, binding.height as i32)
+			)));
+		}
+		
+		return rects;
+	}
+	
+	
+	/// Scales every box's offsets and size by numerator / denominator, rounding to the
+	/// nearest pixel. Box types and cutout offsets are left alone. Results outside the
+	/// field ranges are clamped, with a warning.
+	#[func] pub fn scale_boxes(&mut self, numerator: i32, denominator: i32) {
+		if denominator == 0 {
+			log_error!("Cell::scale_boxes() error: Denominator cannot be 0");
+			return;
+		}
+		
+		let mut clamped: bool = false;
+		
+		// Round half away from zero
+		let mut scale = |value: i64, min: i64, max: i64| -> i64 {
+			let mut product: i64 = value * numerator as i64;
+			let mut denominator: i64 = denominator as i64;
+			
+			if denominator < 0 {
+				product = -product;
+				denominator = -denominator;
+			}
+			
+			let scaled: i64 = (product + (denominator / 2) * product.signum()) / denominator;
+			
+			if scaled < min || scaled > max {
+				clamped = true;
+			}
+			
+			return scaled.clamp(min, max);
+		};
+		
+		for mut hitbox in self.boxes.iter_shared() {
+			let mut binding = hitbox.bind_mut();
+			binding.x_offset = scale(binding.x_offset as i64, i16::MIN as i64, i16::MAX as i64) as i16;
+			binding.y_offset = scale(binding.y_offset as i64, i16::MIN as i64, i16::MAX as i64) as i16;
+			binding.width = scale(binding.width as i64, 0, u16::MAX as i64) as u16;
+			binding.height = scale(binding.height as i64, 0, u16::MAX as i64) as u16;
+		}
+		
+		if clamped {
+			log_warn!("Cell::scale_boxes() warning: Some box values were clamped to fit");
+		}
+	}
+	
+	
+	/// Returns a new cell holding copies of this cell's boxes followed by `other`'s,
+	/// with this cell's sprite index, offsets and unknown fields.
+	#[func] pub fn merge(&self, other: Gd<Cell>) -> Gd<Cell> {
+		let mut hitbox_array: Array<Gd<BoxInfo>> = Array::new();
+		
+		for hitbox in self.boxes.iter_shared().chain(other.bind().boxes.iter_shared()) {
+			let binding = hitbox.bind();
+			
+			hitbox_array.push(&Gd::from_init_fn(|base| {
+				BoxInfo {
+					base,
+					x_offset: binding.x_offset,
+					y_offset: binding.y_offset,
+					width: binding.width,
+					height: binding.height,
+					box_type: binding.box_type,
+					crop_x_offset: binding.crop_x_offset,
+					crop_y_offset: binding.crop_y_offset,
+				}
+			}));
+		}
+		
+		return Gd::from_init_fn(|base| {
+			Self {
+				base,
+				boxes: hitbox_array,
+				sprite_x_offset: self.sprite_x_offset,
+				sprite_y_offset: self.sprite_y_offset,
+				unknown_1: self.unknown_1,
+				sprite_index: self.sprite_index,
+				unknown_2: self.unknown_2,
+			}
+		});
+	}
+	
+	
+	/// Saves this cell's boxes as an SVG diagram, in object coordinates, with a crosshair
+	/// at the origin. Each box is filled with `box_colors[box_type]` at partial opacity and
+	/// outlined at full opacity; types without a color are drawn gray.
+	#[func] pub fn save_boxes_svg(&self, box_colors: Array<Color>, path: String) {
+		const CROSSHAIR: i32 = 8;
+		
+		let mut left: i32 = -CROSSHAIR;
+		let mut top: i32 = -CROSSHAIR;
+		let mut right: i32 = CROSSHAIR;
+		let mut bottom: i32 = CROSSHAIR;
+		let mut rects: String = String::new();
+		
+		for hitbox in self.boxes.iter_shared() {
+			let binding = hitbox.bind();
+			let x: i32 = binding.x_offset as i32;
+			let y: i32 = binding.y_offset as i32;
+			let width: i32 = binding.width as i32;
+			let height: i32 = binding.height as i32;
+			
+			left = std::cmp::min(left, x);
+			top = std::cmp::min(top, y);
+			right = std::cmp::max(right, x + width);
+			bottom = std::cmp::max(bottom, y + height);
+			
+			let color: Color = box_colors.get(binding.box_type as usize).unwrap_or(Color::GRAY);
+			let hex: String = format!("#{:02x}{:02x}{:02x}", color.r8(), color.g8(), color.b8());
+			
+			rects.push_str(&format!(
+				"\t<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" fill-opacity=\"0.25\" stroke=\"{}\" stroke-width=\"1\"><title>Type {}</title></rect>\n",
+				x, y, width, height, hex, hex, binding.box_type
+			));
+		}
+		
+		let mut svg: String = format!(
+			"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n",
+			left - 1, top - 1, right - left + 2, bottom - top + 2, right - left + 2, bottom - top + 2
+		);
+		
+		svg.push_str(&rects);
+		svg.push_str(&format!(
+			"\t<path d=\"M {} 0 H {} M 0 {} V {}\" stroke=\"#ffffff\" stroke-width=\"1\"/>\n",
+			-CROSSHAIR, CROSSHAIR, -CROSSHAIR, CROSSHAIR
+		));
+		svg.push_str("</svg>\n");
+		
+		let path_buf: PathBuf = PathBuf::from(path);
+		
+		match fs::File::create(&path_buf) {
+			Ok(file) => {
+				let ref mut buffer = BufWriter::new(file);
+				let _ = buffer.write_all(svg.as_bytes());
+				let _ = buffer.flush();
+			},
+			
+			_ => log_error!("Could not create SVG file!"),
+		}
+	}
+	
+	
+	/// Mirrors the cell horizontally about the object's origin: boxes and the sprite offset
+	/// are moved so that a span [x, x + width] becomes [-(x + width), -x]. For type 3/6 boxes
+	/// the cutout offset is mirrored within a sprite of `sprite_width` pixels, rounded to
+	/// the game's 8 pixel steps. The sprite's pixels are not flipped.
+	#[func] pub fn flip_h(&mut self, sprite_width: u16) {
+		for mut hitbox in self.boxes.iter_shared() {
+			let mut binding = hitbox.bind_mut();
+			binding.x_offset = flip_span(binding.x_offset, binding.width);
+			
+			if binding.box_type == 3 || binding.box_type == 6 {
+				binding.crop_x_offset = flip_crop_offset(binding.crop_x_offset, binding.width, sprite_width);
+			}
+		}
+		
+		self.sprite_x_offset = flip_span(self.sprite_x_offset, sprite_width);
 	}
 	
 	
@@ -331,4 +634,374 @@ impl Cell {
 	#[func] pub fn clamp_sprite_index(&mut self, sprite_max: u16) {
 		self.sprite_index = self.sprite_index.clamp(0, sprite_max);
 	}
+	
+	
+	/// Renders the sprite at this cell's offsets, scaled and then rotated about the object's origin.
+	/// Rotation is in degrees, clockwise. The canvas is expanded to fit the transformed sprite.
+	/// Uses the sprite's embedded palette if `palette` is empty, grayscale if both are.
+	#[func] pub fn render_texture_transformed(
+		&self,
+		sprite: Gd<BinSprite>,
+		palette: PackedByteArray,
+		scale_x: f32,
+		scale_y: f32,
+		rotation_deg: f32,
+	) -> Gd<ImageTexture> {
+		let binding = sprite.bind();
+		let mut width: usize = 0;
+		let mut height: usize = 0;
+		
+		match &binding.image {
+			Some(image) => {
+				width = image.get_width() as usize;
+				height = image.get_height() as usize;
+			},
+			
+			None => (),
+		}
+		
+		// Nothing to draw
+		if width == 0 || height == 0 || scale_x == 0.0 || scale_y == 0.0 {
+			let empty: Gd<Image> = Image::create_empty(1, 1, false, Format::RGBA8).unwrap();
+			return ImageTexture::create_from_image(&empty).unwrap();
+		}
+		
+		let mut source_palette: Vec<u8>;
+		
+		if !palette.is_empty() {
+			source_palette = palette.to_vec();
+		}
+		
+		else if !binding.palette.is_empty() {
+			source_palette = binding.palette.to_vec();
+		}
+		
+		else {
+			let color_count: usize = 2usize.pow(binding.bit_depth as u32);
+			source_palette = Vec::with_capacity(4 * color_count);
+			
+			for index in 0..color_count {
+				let gray: u8 = (index * 255 / (color_count - 1).max(1)) as u8;
+				source_palette.extend_from_slice(&[gray, gray, gray, 0x80]);
+			}
+			
+			source_palette[3] = 0x00;
+		}
+		
+		// Palettes store full opacity as 0x80
+		for color in 0..source_palette.len() / 4 {
+			let alpha: usize = 4 * color + 3;
+			
+			if source_palette[alpha] >= 0x80 {
+				source_palette[alpha] = 0xFF;
+			} else {
+				source_palette[alpha] = source_palette[alpha] * 2;
+			}
+		}
+		
+		let source_rgba: Vec<u8> = sprite_transform::indexed_to_rgba(
+			&binding.pixels.to_vec(), &source_palette, binding.bit_depth
+		);
+		
+		let radians: f32 = rotation_deg.to_radians();
+		let cos: f32 = radians.cos();
+		let sin: f32 = radians.sin();
+		
+		let left: f32 = self.sprite_x_offset as f32;
+		let top: f32 = self.sprite_y_offset as f32;
+		let right: f32 = left + width as f32;
+		let bottom: f32 = top + height as f32;
+		
+		// Transformed sprite corners give the canvas extents
+		let mut min_x: f32 = f32::MAX;
+		let mut min_y: f32 = f32::MAX;
+		let mut max_x: f32 = f32::MIN;
+		let mut max_y: f32 = f32::MIN;
+		
+		for corner in [(left, top), (right, top), (left, bottom), (right, bottom)] {
+			let scaled_x: f32 = corner.0 * scale_x;
+			let scaled_y: f32 = corner.1 * scale_y;
+			let x: f32 = scaled_x * cos - scaled_y * sin;
+			let y: f32 = scaled_x * sin + scaled_y * cos;
+			
+			min_x = min_x.min(x);
+			min_y = min_y.min(y);
+			max_x = max_x.max(x);
+			max_y = max_y.max(y);
+		}
+		
+		let canvas_x: i32 = min_x.floor() as i32;
+		let canvas_y: i32 = min_y.floor() as i32;
+		let canvas_w: usize = std::cmp::max(max_x.ceil() as i32 - canvas_x, 1) as usize;
+		let canvas_h: usize = std::cmp::max(max_y.ceil() as i32 - canvas_y, 1) as usize;
+		
+		// Inverse mapping, nearest neighbor
+		let mut canvas: Vec<u8> = vec![0u8; canvas_w * canvas_h * 4];
+		
+		for canvas_row in 0..canvas_h {
+			for canvas_column in 0..canvas_w {
+				let x: f32 = (canvas_x + canvas_column as i32) as f32 + 0.5;
+				let y: f32 = (canvas_y + canvas_row as i32) as f32 + 0.5;
+				
+				let source_x: f32 = (x * cos + y * sin) / scale_x - left;
+				let source_y: f32 = (y * cos - x * sin) / scale_y - top;
+				
+				if source_x < 0.0 || source_y < 0.0 {
+					continue;
+				}
+				
+				let pixel_x: usize = source_x as usize;
+				let pixel_y: usize = source_y as usize;
+				
+				if pixel_x >= width || pixel_y >= height {
+					continue;
+				}
+				
+				let source: usize = 4 * (pixel_y * width + pixel_x);
+				let target: usize = 4 * (canvas_row * canvas_w + canvas_column);
+				
+				if source + 3 < source_rgba.len() {
+					canvas[target..target + 4].copy_from_slice(&source_rgba[source..source + 4]);
+				}
+			}
+		}
+		
+		let image: Gd<Image> = Image::create_from_data(
+			canvas_w as i32,
+			canvas_h as i32,
+			// Mipmapping
+			false,
+			Format::RGBA8,
+			&PackedByteArray::from(canvas)
+		).unwrap();
+		
+		return ImageTexture::create_from_image(&image).unwrap();
+	}
+}
+
+
+// Mirrors a span [x, x + width] about the origin, returning the new x: -(x + width).
+fn flip_span(x_offset: i16, width: u16) -> i16 {
+	let right: i32 = x_offset as i32 + width as i32;
+	return (-right).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+}
+
+
+// Mirrors a cutout within a sprite of `sprite_width` pixels. Crop offsets are in
+// 8 pixel steps, so the mirrored offset is rounded to the nearest one.
+fn flip_crop_offset(crop_x_offset: u8, width: u16, sprite_width: u16) -> u8 {
+	let crop_right: i32 = 8 * crop_x_offset as i32 + width as i32;
+	let mirrored: i32 = sprite_width as i32 - crop_right;
+	return ((mirrored + 4).div_euclid(8)).clamp(0, u8::MAX as i32) as u8;
+}
+
+
+// Encodes a cell as the game stores it. Each box_type holds the box's type in its low
+// 16 bits and the crop offsets above, the same layout as the box's last 4 bytes.
+fn cell_json_to_bin(cell_json: &CellJSON) -> Vec<u8> {
+	let mut bin_data: Vec<u8> = Vec::new();
+	
+	// Hitbox count
+	bin_data.extend((cell_json.boxes.len() as u32).to_le_bytes());
+	
+	// Hitbox data
+	for hitbox in cell_json.boxes.iter() {
+		bin_data.extend(hitbox.x_offset.to_le_bytes());
+		bin_data.extend(hitbox.y_offset.to_le_bytes());
+		bin_data.extend(hitbox.width.to_le_bytes());
+		bin_data.extend(hitbox.height.to_le_bytes());
+		bin_data.extend(hitbox.box_type.to_le_bytes());
+	}
+	
+	// Rest of data
+	bin_data.extend(cell_json.sprite_info.x_offset.to_le_bytes());
+	bin_data.extend(cell_json.sprite_info.y_offset.to_le_bytes());
+	bin_data.extend(cell_json.sprite_info.unk.to_le_bytes());
+	bin_data.extend(cell_json.sprite_info.index.to_le_bytes());
+	bin_data.extend(cell_json.sprite_info.unk_2.to_le_bytes());
+	
+	// Pad and align
+	while bin_data.len() % 0x10 != 0x00 {
+		bin_data.push(0xFF);
+	}
+	
+	return bin_data;
+}
+
+
+// Decodes a cell from binary data, or None if the data is too short or corrupt.
+fn cell_json_from_bin(bin_data: &[u8]) -> Option<CellJSON> {
+	if bin_data.len() < 0x04 {
+		return None;
+	}
+
+	let hitbox_count: u32 = u32::from_le_bytes(
+		[bin_data[0x00], bin_data[0x01], bin_data[0x02], bin_data[0x03]]
+	);
+	
+	if hitbox_count > MAX_HITBOX_COUNT {
+		log_error!("Cell::from_binary_data() error: Hitbox count {} exceeds maximum {}", hitbox_count, MAX_HITBOX_COUNT);
+		return None;
+	}
+	
+	if bin_data.len() < (0x0C * hitbox_count as usize) + 0x10 {
+		return None;
+	}
+	
+	let mut boxes: Vec<CellJSONBox> = Vec::new();
+	for hitbox_number in 0..hitbox_count as usize {
+		let cursor: usize = 0x0C * hitbox_number + 0x04;
+		
+		boxes.push(CellJSONBox {
+			x_offset: i16::from_le_bytes([bin_data[cursor + 0x00], bin_data[cursor + 0x01]]),
+			y_offset: i16::from_le_bytes([bin_data[cursor + 0x02], bin_data[cursor + 0x03]]),
+			width: u16::from_le_bytes([bin_data[cursor + 0x04], bin_data[cursor + 0x05]]),
+			height: u16::from_le_bytes([bin_data[cursor + 0x06], bin_data[cursor + 0x07]]),
+			box_type: u32::from_le_bytes([
+				bin_data[cursor + 0x08], bin_data[cursor + 0x09],
+				bin_data[cursor + 0x0A], bin_data[cursor + 0x0B]
+			]),
+		});
+	}
+	
+	let cursor: usize = (hitbox_count as usize) * 0x0C + 0x04;
+	return Some(CellJSON {
+		boxes,
+		sprite_info: CellJSONSpriteInfo {
+			x_offset: i16::from_le_bytes([bin_data[cursor + 0x00], bin_data[cursor + 0x01]]),
+			y_offset: i16::from_le_bytes([bin_data[cursor + 0x02], bin_data[cursor + 0x03]]),
+			unk: u32::from_le_bytes([
+				bin_data[cursor + 0x04], bin_data[cursor + 0x05],
+				bin_data[cursor + 0x06], bin_data[cursor + 0x07]
+			]),
+			index: u16::from_le_bytes([bin_data[cursor + 0x08], bin_data[cursor + 0x09]]),
+			unk_2: u16::from_le_bytes([bin_data[cursor + 0x0A], bin_data[cursor + 0x0B]]),
+		},
+	});
+}
+
+
+// Parses a single cell, as written to cell_#.json.
+fn parse_cell(string: &str) -> Option<CellJSON> {
+	match serde_json::from_str(string) {
+		Ok(cell) => return Some(cell),
+		_ => return None,
+	}
+}
+
+
+// Parses an array of cells, as written to cells.json.
+fn parse_cell_array(string: &str) -> Option<Vec<CellJSON>> {
+	match serde_json::from_str(string) {
+		Ok(cells) => return Some(cells),
+		_ => return None,
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	#[test]
+	fn absurd_hitbox_count_is_rejected() {
+		let mut bin_data: Vec<u8> = vec![0xFF, 0xFF, 0xFF, 0x7F];
+		bin_data.resize(0x40, 0x00);
+		assert!(Cell::from_binary_data(&bin_data).is_none());
+	}
+	
+	
+	#[test]
+	fn short_cell_data_is_rejected() {
+		// Two hitboxes need 0x28 bytes
+		let mut bin_data: Vec<u8> = vec![0x02, 0x00, 0x00, 0x00];
+		bin_data.resize(0x20, 0x00);
+		assert!(Cell::from_binary_data(&bin_data).is_none());
+		
+		assert!(Cell::from_binary_data(&[0x00, 0x00]).is_none());
+	}
+	
+	
+	fn test_cell(index: u16) -> CellJSON {
+		return CellJSON {
+			boxes: vec![CellJSONBox {
+				x_offset: -12,
+				y_offset: 40,
+				width: 24,
+				height: 16,
+				box_type: 0x0203_0001,
+			}],
+			sprite_info: CellJSONSpriteInfo {
+				index,
+				unk: 0x10,
+				x_offset: -64,
+				y_offset: -128,
+				unk_2: 3,
+			},
+		};
+	}
+	
+	
+	#[test]
+	fn cell_array_matches_cell_files() {
+		let cells: Vec<CellJSON> = vec![test_cell(0), test_cell(1)];
+		let array_string: String = serde_json::to_string_pretty(&cells).unwrap();
+		let from_array: Vec<CellJSON> = parse_cell_array(&array_string).unwrap();
+		
+		for (index, cell) in cells.iter().enumerate() {
+			let file_string: String = serde_json::to_string_pretty(cell).unwrap();
+			assert_eq!(parse_cell(&file_string).unwrap(), from_array[index]);
+		}
+		
+		assert_eq!(from_array, cells);
+	}
+	
+	
+	#[test]
+	fn unusual_box_types_round_trip() {
+		let mut cell: CellJSON = test_cell(5);
+		
+		// Box types past the known ones, with crop offsets in the upper bytes
+		for box_type in [0x0000_0007, 0x00FF_00FF, 0xFF10_0007] {
+			cell.boxes.push(CellJSONBox {
+				x_offset: 8,
+				y_offset: -8,
+				width: 4,
+				height: 4,
+				box_type,
+			});
+		}
+		
+		cell.sprite_info.unk_2 = 0xBEEF;
+		
+		let from_json: CellJSON = parse_cell(&serde_json::to_string_pretty(&cell).unwrap()).unwrap();
+		assert_eq!(from_json, cell);
+		
+		let bin_data: Vec<u8> = cell_json_to_bin(&cell);
+		assert_eq!(bin_data.len() % 0x10, 0);
+		assert_eq!(&bin_data[0x10 + 0x08..0x10 + 0x0C], &[0x07, 0x00, 0x00, 0x00]);
+		assert_eq!(&bin_data[0x28 + 0x08..0x28 + 0x0C], &[0x07, 0x00, 0x10, 0xFF]);
+		assert_eq!(cell_json_from_bin(&bin_data).unwrap(), cell);
+	}
+	
+	
+	#[test]
+	fn flipping_twice_restores_offsets() {
+		for (x_offset, width) in [(5, 10), (-10, 20), (-64, 0), (0, 96)] {
+			assert_eq!(flip_span(flip_span(x_offset, width), width), x_offset);
+		}
+		
+		assert_eq!(flip_span(5, 10), -15);
+		
+		// 64px sprite, 12px cutout at 16px: mirrored to 36px, rounded to 40px
+		assert_eq!(flip_crop_offset(2, 12, 64), 5);
+		
+		for crop_x_offset in 0..6 {
+			for width in [8, 12, 16] {
+				let flipped: u8 = flip_crop_offset(crop_x_offset, width, 64);
+				assert_eq!(flip_crop_offset(flipped, width, 64), crop_x_offset);
+			}
+		}
+	}
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 use godot::prelude::*;
 
+use crate::logging::*;
+
 pub const ENCRYPTED_SIGNATURE: u32 = 0x41534743;
 const CHARIDX_SIGNATURE: u32 = 0x082A2000;
 const WII_TPL_SIGNATURE: u32 = 0x0020AF30;
@@ -31,6 +33,7 @@ pub enum ObjectType {
 	SpriteListSelect,	// Good		-- Covered
 	JPFPlainText,		// Good		-- Covered, currently partially unsupported
 	WiiTPL,				// Good		-- Covered, currently unsupported
+	Palette,			// Good		-- Covered
 	Scriptable,			// Good		-- Covered, currently partially unsupported
 	MultiScriptable,	// Good		-- Covered
 	Unsupported,
@@ -42,7 +45,23 @@ pub enum ObjectType {
 // =================================================================================
 
 
-pub fn get_pointers(bin_data: &Vec<u8>, mut cursor: usize, big_endian: bool) -> Vec<usize> {
+fn read_pointer(bin_data: &[u8], cursor: usize, big_endian: bool) -> usize {
+	let bytes: [u8; 4] = [
+		bin_data[cursor + 0x00],
+		bin_data[cursor + 0x01],
+		bin_data[cursor + 0x02],
+		bin_data[cursor + 0x03]
+	];
+	
+	if big_endian {
+		return u32::from_be_bytes(bytes) as usize;
+	}
+	
+	return u32::from_le_bytes(bytes) as usize;
+}
+
+
+pub fn get_pointers(bin_data: &[u8], mut cursor: usize, big_endian: bool) -> Vec<usize> {
 	let mut pointers: Vec<usize> = Vec::new();
 	
 	loop {
@@ -50,30 +69,53 @@ pub fn get_pointers(bin_data: &Vec<u8>, mut cursor: usize, big_endian: bool) ->
 			break;
 		}
 		
-		let pointer: usize;
+		let pointer: usize = read_pointer(bin_data, cursor, big_endian);
 		
-		if big_endian {
-			pointer = u32::from_be_bytes([
-				bin_data[cursor + 0x00],
-				bin_data[cursor + 0x01],
-				bin_data[cursor + 0x02],
-				bin_data[cursor + 0x03]
-			]) as usize;
+		if pointer == 0xFFFFFFFF {
+			break;
 		}
 		
-		else {
-			pointer = u32::from_le_bytes([
-				bin_data[cursor + 0x00],
-				bin_data[cursor + 0x01],
-				bin_data[cursor + 0x02],
-				bin_data[cursor + 0x03]
-			]) as usize;
+		pointers.push(pointer);
+		cursor += 4;
+	}
+	
+	return pointers;
+}
+
+
+// Like get_pointers, but also stops at the first pointer that is out of bounds or
+// lower than the one before it. Guards against tables missing their terminator.
+pub fn get_pointers_validated(bin_data: &[u8], mut cursor: usize, big_endian: bool) -> Vec<usize> {
+	let mut pointers: Vec<usize> = Vec::new();
+	
+	loop {
+		if cursor + 3 >= bin_data.len() {
+			log_warn!("bin_identify::get_pointers_validated() warning: Pointer table is not terminated");
+			break;
 		}
 		
+		let pointer: usize = read_pointer(bin_data, cursor, big_endian);
+		
 		if pointer == 0xFFFFFFFF {
 			break;
 		}
 		
+		if pointer >= bin_data.len() {
+			log_warn!(
+				"bin_identify::get_pointers_validated() warning: Pointer #{} (0x{:X}) is out of bounds, truncating table",
+				pointers.len(), pointer
+			);
+			break;
+		}
+		
+		if pointers.len() > 0 && pointer < pointers[pointers.len() - 1] {
+			log_warn!(
+				"bin_identify::get_pointers_validated() warning: Pointer #{} (0x{:X}) is not increasing, truncating table",
+				pointers.len(), pointer
+			);
+			break;
+		}
+		
 		pointers.push(pointer);
 		cursor += 4;
 	}
@@ -87,13 +129,13 @@ pub fn get_pointers(bin_data: &Vec<u8>, mut cursor: usize, big_endian: bool) ->
 // =================================================================================
 
 
-pub fn identify_audio_wbnd(bin_data: &Vec<u8>) -> bool {
+pub fn identify_audio_wbnd(bin_data: &[u8]) -> bool {
 	let pointers: Vec<usize> = get_pointers(&bin_data, 0x00, true);
 	return pointers[0] == WBND_SIGNATURE;
 }
 
 
-pub fn identify_audio_vagp(bin_data: &Vec<u8>) -> bool {
+pub fn identify_audio_vagp(bin_data: &[u8]) -> bool {
 	let pointers: Vec<usize> = get_pointers(&bin_data, 0x00, true);
 	if bin_data.len() <= pointers[0] {
 		return false;
@@ -108,7 +150,18 @@ pub fn identify_audio_vagp(bin_data: &Vec<u8>) -> bool {
 }
 
 
-pub fn identify_sprite(bin_data: &Vec<u8>) -> bool {
+pub fn identify_palette(bin_data: &[u8]) -> bool {
+	if bin_data.len() < 0x10 {
+		return false;
+	}
+	
+	return u32::from_be_bytes([
+		bin_data[0x00], bin_data[0x01], bin_data[0x02], bin_data[0x03],
+	]) == PALETTE_SIGNATURE;
+}
+
+
+pub fn identify_sprite(bin_data: &[u8]) -> bool {
 	if bin_data.len() < 0x20 {
 		return false;
 	}
@@ -123,7 +176,7 @@ pub fn identify_sprite(bin_data: &Vec<u8>) -> bool {
 }
 
 
-pub fn identify_sprite_list_select(bin_data: &Vec<u8>) -> bool {
+pub fn identify_sprite_list_select(bin_data: &[u8]) -> bool {
 	if !identify_sprite_list(bin_data) {
 		return false;
 	}
@@ -149,7 +202,7 @@ pub fn identify_sprite_list_select(bin_data: &Vec<u8>) -> bool {
 }
 
 
-pub fn identify_sprite_list(bin_data: &Vec<u8>) -> bool {
+pub fn identify_sprite_list(bin_data: &[u8]) -> bool {
 	let header_pointers: Vec<usize> = get_pointers(bin_data, 0x00, false);
 	
 	if header_pointers.len() < 1 {
@@ -172,7 +225,7 @@ pub fn identify_sprite_list(bin_data: &Vec<u8>) -> bool {
 }
 
 
-pub fn identify_jpf_plain_text(bin_data: &Vec<u8>) -> bool {
+pub fn identify_jpf_plain_text(bin_data: &[u8]) -> bool {
 	let header_pointers: Vec<usize> = get_pointers(&bin_data, 0x00, false);
 	
 	if header_pointers.len() < 2 {
@@ -208,14 +261,14 @@ pub fn identify_jpf_plain_text(bin_data: &Vec<u8>) -> bool {
 }
 
 
-pub fn identify_wii_tpl(bin_data: &Vec<u8>) -> bool {
+pub fn identify_wii_tpl(bin_data: &[u8]) -> bool {
 	return u32::from_be_bytes([
 		bin_data[0x00], bin_data[0x01], bin_data[0x02], bin_data[0x03],
 	]) == WII_TPL_SIGNATURE;
 }
 
 
-pub fn identify_scriptable(bin_data: &Vec<u8>) -> bool {
+pub fn identify_scriptable(bin_data: &[u8]) -> bool {
 	let header_pointers: Vec<usize> = get_pointers(bin_data, 0x00, false);
 	
 	// Needs at least 1 sprite
@@ -341,7 +394,7 @@ pub fn identify_scriptable(bin_data: &Vec<u8>) -> bool {
 }
 
 
-pub fn identify_multi_scriptable(bin_data: &Vec<u8>) -> bool {
+pub fn identify_multi_scriptable(bin_data: &[u8]) -> bool {
 	let header_pointers: Vec<usize> = get_pointers(bin_data, 0x00, false);
 	
 	for pointer in 0..header_pointers.len() {
@@ -378,7 +431,7 @@ pub fn identify_multi_scriptable(bin_data: &Vec<u8>) -> bool {
 }
 
 
-pub fn identify_object(bin_data: &Vec<u8>) -> ObjectType {
+pub fn identify_object(bin_data: &[u8]) -> ObjectType {
 	if identify_audio_wbnd(bin_data) {
 		return ObjectType::Unsupported;
 	}
@@ -387,6 +440,10 @@ pub fn identify_object(bin_data: &Vec<u8>) -> ObjectType {
 		return ObjectType::Unsupported;
 	}
 	
+	if identify_palette(bin_data) {
+		return ObjectType::Palette;
+	}
+	
 	if identify_sprite(bin_data) {
 		return ObjectType::Sprite;
 	}
@@ -416,4 +473,49 @@ pub fn identify_object(bin_data: &Vec<u8>) -> ObjectType {
 	}
 	
 	return ObjectType::Unsupported;
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	fn pointer_table(words: &[u32]) -> Vec<u8> {
+		let mut bin_data: Vec<u8> = Vec::new();
+		
+		for word in words {
+			bin_data.extend_from_slice(&word.to_le_bytes());
+		}
+		
+		return bin_data;
+	}
+	
+	
+	#[test]
+	fn terminated_table() {
+		let bin_data: Vec<u8> = pointer_table(&[0x08, 0x0C, 0xFFFFFFFF, 0x00]);
+		assert_eq!(get_pointers_validated(&bin_data, 0x00, false), vec![0x08, 0x0C]);
+	}
+	
+	
+	#[test]
+	fn missing_terminator_stops_at_end_of_data() {
+		let bin_data: Vec<u8> = pointer_table(&[0x0C, 0x0D, 0x0E, 0x0F]);
+		assert_eq!(get_pointers_validated(&bin_data, 0x00, false), vec![0x0C, 0x0D, 0x0E, 0x0F]);
+	}
+	
+	
+	#[test]
+	fn out_of_bounds_pointer_truncates_table() {
+		let bin_data: Vec<u8> = pointer_table(&[0x0C, 0x100, 0xFFFFFFFF, 0x00]);
+		assert_eq!(get_pointers_validated(&bin_data, 0x00, false), vec![0x0C]);
+	}
+	
+	
+	#[test]
+	fn decreasing_pointer_truncates_table() {
+		let bin_data: Vec<u8> = pointer_table(&[0x0C, 0x08, 0xFFFFFFFF, 0x00]);
+		assert_eq!(get_pointers_validated(&bin_data, 0x00, false), vec![0x0C]);
+	}
 }
\ No newline at end of file
@@ -9,6 +9,7 @@ use godot::prelude::*;
 use crate::sprite_get;
 use crate::sprite_transform;
 use crate::sprite_compress::SpriteData;
+use crate::logging::*;
 
 
 #[derive(GodotClass)]
@@ -53,7 +54,7 @@ impl BinPalette {
 
 	pub fn from_bin_file_pathbuf(path_buf: PathBuf) -> Option<Gd<Self>> {
 		if !path_buf.exists() {
-			godot_print!("Could not find palette file!");
+			log_error!("Could not find palette file!");
 			return None;
 		}
 		
@@ -61,7 +62,7 @@ impl BinPalette {
 			Ok(data) => return Self::from_bin_data(data),
 			
 			_ => {
-				godot_print!("Could not load palette file!");
+				log_error!("Could not load palette file!");
 				return None;
 			},
 		}
@@ -70,21 +71,33 @@ impl BinPalette {
 
 	// Loads BinPalettes from a raw binary data vector.
 	pub fn from_bin_data(bin_data: Vec<u8>) -> Option<Gd<BinPalette>> {
+		if bin_data.len() < 0x10 {
+			log_error!("BIN data is too short to contain a palette.");
+			return None;
+		}
+		
 		// Check 'clut' byte
 		if bin_data[0x02] != 0x20 {
-			godot_print!("BIN data does not contain a palette.");
+			log_error!("BIN data does not contain a palette.");
 			return None;
 		}
 		
 		// Get palette
-		let palette: Vec<u8>;
+		let palette_end: usize;
 		
 		if bin_data[0x04] == 0x04 {
-			palette = bin_data[0x10..0x50].to_vec();
+			palette_end = 0x50;
 		} else {
-			palette = bin_data[0x10..0x410].to_vec();
+			palette_end = 0x410;
 		}
 		
+		if bin_data.len() < palette_end {
+			log_error!("BIN data is too short for its declared palette size.");
+			return None;
+		}
+		
+		let palette: Vec<u8> = bin_data[0x10..palette_end].to_vec();
+		
 		return Some(
 			Gd::from_init_fn(|base| {
 				BinPalette {
@@ -103,15 +116,15 @@ impl BinPalette {
 		let path_buf: PathBuf = PathBuf::from(path_str);
 		
 		if !path_buf.exists() {
-			godot_print!("Could not find palette file!");
+			log_error!("Could not find palette file!");
 			return None;
 		}
 		
 		let sprite_data: SpriteData;
 		
 		match sprite_get::get_png(&path_buf) {
-			None => return None,
-			Some(data) => sprite_data = data,
+			Err(_) => return None,
+			Ok(data) => sprite_data = data,
 		}
 		
 		if sprite_data.palette.is_empty() {
@@ -136,15 +149,15 @@ impl BinPalette {
 		let path_buf: PathBuf = PathBuf::from(path_str);
 		
 		if !path_buf.exists() {
-			godot_print!("Could not find palette file!");
+			log_error!("Could not find palette file!");
 			return None;
 		}
 		
 		let sprite_data: SpriteData;
 		
 		match sprite_get::get_bmp(&path_buf) {
-			None => return None,
-			Some(data) => sprite_data = data,
+			Err(_) => return None,
+			Ok(data) => sprite_data = data,
 		}
 		
 		if sprite_data.palette.is_empty() {
@@ -169,7 +182,7 @@ impl BinPalette {
 		let path_buf: PathBuf = PathBuf::from(path_str);
 		
 		if !path_buf.exists() {
-			godot_print!("Could not find palette file!");
+			log_error!("Could not find palette file!");
 			return None;
 		}
 		
@@ -178,7 +191,7 @@ impl BinPalette {
 		match fs::read(path_buf) {
 			Ok(data) => {
 				if data.len() < 0x304 {
-					godot_print!("Invalid .ACT file!");
+					log_error!("Invalid .ACT file!");
 					return None;
 				}
 				
@@ -186,7 +199,7 @@ impl BinPalette {
 			},
 			
 			_ => {
-				godot_print!("Errored while reading .ACT file!");
+				log_error!("Errored while reading .ACT file!");
 				return None;
 			}
 		}
@@ -218,6 +231,280 @@ impl BinPalette {
 	}
 	
 	
+	/// Static constructor for BinPalettes from GIMP .gpl files.
+	/// Colors past the 256th are dropped and malformed lines skipped.
+	#[func]
+	pub fn from_gpl_file(path: GString) -> Option<Gd<Self>> {
+		let path_str: String = String::from(path);
+		let path_buf: PathBuf = PathBuf::from(path_str);
+		
+		if !path_buf.exists() {
+			log_error!("Could not find palette file!");
+			return None;
+		}
+		
+		let gpl_text: String;
+		
+		match fs::read_to_string(path_buf) {
+			Ok(text) => gpl_text = text,
+			_ => {
+				log_error!("Errored while reading .GPL file!");
+				return None;
+			}
+		}
+		
+		let mut lines = gpl_text.lines();
+		
+		match lines.next() {
+			Some(line) if line.trim() == "GIMP Palette" => (),
+			_ => {
+				log_error!("Invalid .GPL file!");
+				return None;
+			}
+		}
+		
+		let mut palette: Vec<u8> = Vec::new();
+		let mut color_count: usize = 0;
+		
+		for line in lines {
+			let line: &str = line.trim();
+			
+			if line.is_empty() || line.starts_with('#') || line.starts_with("Name:") || line.starts_with("Columns:") {
+				continue;
+			}
+			
+			// R G B, optionally followed by a color name
+			let channels: Vec<u8> = line.split_whitespace()
+				.take(3)
+				.filter_map(|channel| channel.parse::<u8>().ok())
+				.collect();
+			
+			if channels.len() != 3 {
+				log_info!("Skipping malformed .GPL line: {}", line);
+				continue;
+			}
+			
+			color_count += 1;
+			
+			if color_count > 256 {
+				continue;
+			}
+			
+			palette.extend_from_slice(&channels);
+			
+			// Index #0
+			if color_count == 1 {
+				palette.push(0x00);
+			} else {
+				palette.push(0x80);
+			}
+		}
+		
+		if color_count > 256 {
+			log_info!(".GPL file has {} colors, only the first 256 were kept", color_count);
+		}
+		
+		if palette.is_empty() {
+			log_error!(".GPL file contains no colors!");
+			return None;
+		}
+		
+		return Some(
+			Gd::from_init_fn(|base| {
+				Self {
+					base: base,
+					palette: PackedByteArray::from(palette),
+				}
+			})
+		);
+	}
+	
+	
+	/// Static constructor for BinPalettes from Adobe .ase swatch files.
+	/// RGB, grayscale and CMYK colors are read as RGB; LAB colors and groups are skipped.
+	#[func]
+	pub fn from_ase_file(path: GString) -> Option<Gd<Self>> {
+		let path_str: String = String::from(path);
+		let path_buf: PathBuf = PathBuf::from(path_str);
+		
+		if !path_buf.exists() {
+			log_error!("Could not find palette file!");
+			return None;
+		}
+		
+		let ase_data: Vec<u8>;
+		
+		match fs::read(path_buf) {
+			Ok(data) => {
+				if data.len() < 12 || &data[0..4] != b"ASEF" {
+					log_error!("Invalid .ASE file!");
+					return None;
+				}
+				
+				ase_data = data;
+			},
+			
+			_ => {
+				log_error!("Errored while reading .ASE file!");
+				return None;
+			}
+		}
+		
+		let read_u16 = |at: usize| u16::from_be_bytes([ase_data[at], ase_data[at + 1]]) as usize;
+		let read_u32 = |at: usize| u32::from_be_bytes([
+			ase_data[at], ase_data[at + 1], ase_data[at + 2], ase_data[at + 3]
+		]) as usize;
+		let read_f32 = |at: usize| f32::from_be_bytes([
+			ase_data[at], ase_data[at + 1], ase_data[at + 2], ase_data[at + 3]
+		]);
+		let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+		
+		let block_count: usize = read_u32(8);
+		let mut cursor: usize = 12;
+		let mut palette: Vec<u8> = Vec::new();
+		
+		for _block in 0..block_count {
+			if cursor + 6 > ase_data.len() {
+				log_warn!(".ASE file is truncated, keeping the colors read so far");
+				break;
+			}
+			
+			let block_type: usize = read_u16(cursor);
+			let block_length: usize = read_u32(cursor + 2);
+			let block_start: usize = cursor + 6;
+			cursor = block_start + block_length;
+			
+			// Group start and end blocks carry no colors
+			if block_type != 0x0001 {
+				continue;
+			}
+			
+			if cursor > ase_data.len() || block_length < 2 {
+				log_warn!(".ASE file is truncated, keeping the colors read so far");
+				break;
+			}
+			
+			// Skip the UTF-16 name
+			let model_start: usize = block_start + 2 + 2 * read_u16(block_start);
+			
+			if model_start + 4 > cursor {
+				continue;
+			}
+			
+			let values: usize = model_start + 4;
+			let rgb: [u8; 3];
+			
+			match &ase_data[model_start..model_start + 4] {
+				b"RGB " if values + 12 <= cursor => rgb = [
+					to_byte(read_f32(values)),
+					to_byte(read_f32(values + 4)),
+					to_byte(read_f32(values + 8)),
+				],
+				
+				b"Gray" if values + 4 <= cursor => {
+					let gray: u8 = to_byte(read_f32(values));
+					rgb = [gray, gray, gray];
+				},
+				
+				b"CMYK" if values + 16 <= cursor => {
+					let key: f32 = 1.0 - read_f32(values + 12);
+					rgb = [
+						to_byte((1.0 - read_f32(values)) * key),
+						to_byte((1.0 - read_f32(values + 4)) * key),
+						to_byte((1.0 - read_f32(values + 8)) * key),
+					];
+				},
+				
+				_ => {
+					log_warn!("Skipping .ASE color with unsupported color model");
+					continue;
+				},
+			}
+			
+			if palette.len() == 0x400 {
+				log_info!(".ASE file has more than 256 colors, only the first 256 were kept");
+				break;
+			}
+			
+			palette.extend_from_slice(&rgb);
+			
+			// Index #0
+			if palette.len() == 3 {
+				palette.push(0x00);
+			} else {
+				palette.push(0x80);
+			}
+		}
+		
+		if palette.is_empty() {
+			log_error!(".ASE file contains no usable colors!");
+			return None;
+		}
+		
+		return Some(
+			Gd::from_init_fn(|base| {
+				Self {
+					base: base,
+					palette: PackedByteArray::from(palette),
+				}
+			})
+		);
+	}
+	
+	
+	/// Saves the palette to an Adobe .ase swatch file as ungrouped RGB colors. Alpha is dropped.
+	#[func]
+	pub fn to_ase_file(&self, path: String) {
+		let path_buf: PathBuf = PathBuf::from(path);
+		let mut dir_buf: PathBuf = path_buf.clone();
+		let _ = dir_buf.pop();
+		let _ = fs::create_dir_all(dir_buf);
+		
+		let color_count: usize = self.palette.len() / 4;
+		let mut ase_data: Vec<u8> = Vec::new();
+		
+		// Signature, version 1.0, block count
+		ase_data.extend_from_slice(b"ASEF");
+		ase_data.extend_from_slice(&[0x00, 0x01, 0x00, 0x00]);
+		ase_data.extend_from_slice(&(color_count as u32).to_be_bytes());
+		
+		for color in 0..color_count {
+			let name: Vec<u16> = format!("Index {}", color).encode_utf16().chain([0u16]).collect();
+			let mut block: Vec<u8> = Vec::new();
+			
+			block.extend_from_slice(&(name.len() as u16).to_be_bytes());
+			
+			for character in name {
+				block.extend_from_slice(&character.to_be_bytes());
+			}
+			
+			block.extend_from_slice(b"RGB ");
+			
+			for channel in 0..3 {
+				let value: f32 = self.palette[4 * color + channel] as f32 / 255.0;
+				block.extend_from_slice(&value.to_be_bytes());
+			}
+			
+			// Normal (non-spot, non-global) color
+			block.extend_from_slice(&[0x00, 0x02]);
+			
+			ase_data.extend_from_slice(&[0x00, 0x01]);
+			ase_data.extend_from_slice(&(block.len() as u32).to_be_bytes());
+			ase_data.extend(block);
+		}
+		
+		match File::create(&path_buf) {
+			Ok(file) => {
+				let ref mut buffer = BufWriter::new(file);
+				let _ = buffer.write_all(&ase_data);
+				let _ = buffer.flush();
+			},
+			
+			_ => log_error!("Could not create .ASE file!"),
+		}
+	}
+	
+	
 	/// Saves the palette to an .act file.
 	#[func]
 	pub fn to_act_file(&self, path: String) {
@@ -253,6 +540,42 @@ impl BinPalette {
 	}
 	
 	
+	/// Saves the palette to a GIMP .gpl file, one line per color. Alpha is dropped.
+	#[func]
+	pub fn to_gpl_file(&self, path: String) {
+		let path_buf: PathBuf = PathBuf::from(path);
+		let mut dir_buf: PathBuf = path_buf.clone();
+		let _ = dir_buf.pop();
+		let _ = fs::create_dir_all(dir_buf);
+		
+		let name: String = path_buf.file_stem()
+			.map_or(String::from("Palette"), |stem| stem.to_string_lossy().to_string());
+		let color_count: usize = self.palette.len() / 4;
+		
+		let mut gpl_text: String = format!("GIMP Palette\nName: {}\nColumns: 16\n#\n", name);
+		
+		for color in 0..color_count {
+			gpl_text.push_str(&format!(
+				"{:3} {:3} {:3}\tIndex {}\n",
+				self.palette[4 * color + 0],
+				self.palette[4 * color + 1],
+				self.palette[4 * color + 2],
+				color
+			));
+		}
+		
+		match File::create(&path_buf) {
+			Ok(file) => {
+				let ref mut buffer = BufWriter::new(file);
+				let _ = buffer.write_all(gpl_text.as_bytes());
+				let _ = buffer.flush();
+			},
+			
+			_ => log_error!("Could not create .GPL file!"),
+		}
+	}
+	
+	
 	/// Saves the palette to a .bin file.
 	#[func]
 	pub fn to_bin_file(&self, path: String) {
@@ -274,7 +597,12 @@ impl BinPalette {
 	
 	
 	pub fn to_bin(&self) -> Vec<u8> {
-		let palette: Vec<u8> = self.palette.to_vec();
+		return Self::palette_to_bin(self.palette.to_vec());
+	}
+	
+	
+	// Prepends the palette header to RGBA palette data.
+	pub fn palette_to_bin(palette: Vec<u8>) -> Vec<u8> {
 		let color_count: usize = palette.len() / 4;
 		let mut header = Self::DEFAULT_HEADER.clone();
 		
@@ -291,6 +619,226 @@ impl BinPalette {
 	}
 	
 	
+	/// Combines several 16-color palettes into one palette of up to 256 colors.
+	/// Each palette occupies its own 16-color line, padded with transparent black if short,
+	/// so a sprite using palette `n` can be offset by 16 * n.
+	/// Colors past the 16th in any palette, and palettes past the 16th, are dropped.
+	#[func]
+	pub fn merge(palettes: Array<Gd<BinPalette>>) -> Gd<Self> {
+		let mut merged: Vec<u8> = Vec::with_capacity(0x400);
+		
+		for (number, bin_palette) in palettes.iter_shared().enumerate() {
+			if number >= 16 {
+				log_warn!("BinPalette::merge() warning: Only the first 16 palettes were merged");
+				break;
+			}
+			
+			let mut palette: Vec<u8> = bin_palette.bind().palette.to_vec();
+			
+			if palette.len() > 0x40 {
+				log_warn!("BinPalette::merge() warning: Palette #{} truncated to 16 colors", number);
+			}
+			
+			palette.resize(0x40, 0x00);
+			merged.extend(palette);
+		}
+		
+		return Gd::from_init_fn(|base| {
+			Self {
+				base: base,
+				palette: PackedByteArray::from(merged),
+			}
+		});
+	}
+	
+	
+	/// Combines palettes into one of up to 256 colors, in order, keeping only the first
+	/// occurrence of each RGBA color. Colors past the 256th unique one are dropped.
+	/// See merge_unique_maps() for re-indexing sprites against the result.
+	#[func]
+	pub fn merge_unique(palettes: Array<Gd<BinPalette>>) -> Gd<Self> {
+		let (merged, _): (Vec<u8>, Vec<Vec<i32>>) = Self::merge_unique_data(&palettes);
+		
+		return Gd::from_init_fn(|base| {
+			Self {
+				base: base,
+				palette: PackedByteArray::from(merged),
+			}
+		});
+	}
+	
+	
+	/// For each palette given to merge_unique(), returns a mapping from its old color index
+	/// to the index in the merged palette. Dropped colors map to -1.
+	#[func]
+	pub fn merge_unique_maps(palettes: Array<Gd<BinPalette>>) -> Array<PackedInt32Array> {
+		let (_, index_maps): (Vec<u8>, Vec<Vec<i32>>) = Self::merge_unique_data(&palettes);
+		return index_maps.into_iter().map(PackedInt32Array::from).collect();
+	}
+	
+	
+	fn merge_unique_data(palettes: &Array<Gd<BinPalette>>) -> (Vec<u8>, Vec<Vec<i32>>) {
+		let mut merged: Vec<u8> = Vec::with_capacity(0x400);
+		let mut index_maps: Vec<Vec<i32>> = Vec::new();
+		let mut dropped: usize = 0;
+		
+		for bin_palette in palettes.iter_shared() {
+			let palette: Vec<u8> = bin_palette.bind().palette.to_vec();
+			let mut index_map: Vec<i32> = Vec::with_capacity(palette.len() / 4);
+			
+			for color in palette.chunks_exact(4) {
+				match merged.chunks_exact(4).position(|existing| existing == color) {
+					Some(index) => index_map.push(index as i32),
+					
+					None => {
+						if merged.len() < 0x400 {
+							index_map.push((merged.len() / 4) as i32);
+							merged.extend_from_slice(color);
+						} else {
+							index_map.push(-1);
+							dropped += 1;
+						}
+					},
+				}
+			}
+			
+			index_maps.push(index_map);
+		}
+		
+		if dropped > 0 {
+			log_warn!("BinPalette::merge_unique() warning: {} colors past the 256th were dropped", dropped);
+		}
+		
+		return (merged, index_maps);
+	}
+	
+	
+	/// Returns the index of the color closest to `color` by RGB distance, ignoring alpha.
+	/// With `ignore_transparent`, colors with an alpha of 0 are skipped.
+	/// Ties resolve to the lowest index. Returns -1 if no color qualifies.
+	#[func]
+	pub fn nearest_index(&self, color: Color, ignore_transparent: bool) -> i64 {
+		let target: [i64; 3] = [color.r8() as i64, color.g8() as i64, color.b8() as i64];
+		let color_count: usize = self.palette.len() / 4;
+		let mut best_index: i64 = -1;
+		let mut best_distance: i64 = i64::MAX;
+		
+		for index in 0..color_count {
+			if ignore_transparent && self.palette[4 * index + 3] == 0 {
+				continue;
+			}
+			
+			let mut distance: i64 = 0;
+			
+			for channel in 0..3 {
+				let difference: i64 = self.palette[4 * index + channel] as i64 - target[channel];
+				distance += difference * difference;
+			}
+			
+			if distance < best_distance {
+				best_distance = distance;
+				best_index = index as i64;
+			}
+		}
+		
+		return best_index;
+	}
+	
+	
+	/// Sorts colors from darkest to brightest by perceived luminance (0.299R + 0.587G + 0.114B).
+	/// With `keep_index_zero`, the transparent slot stays at index 0. Colors of equal
+	/// luminance keep their relative order. Returns a mapping from old index to new index,
+	/// for updating sprites that use this palette.
+	#[func]
+	pub fn sort_by_luminance(&mut self, keep_index_zero: bool) -> PackedInt32Array {
+		let color_count: usize = self.palette.len() / 4;
+		let first: usize = std::cmp::min(keep_index_zero as usize, color_count);
+		let mut order: Vec<usize> = (0..color_count).collect();
+		
+		// Integer weights, scaled by 1000
+		let luminance = |index: usize| -> u32 {
+			return 299 * self.palette[4 * index + 0] as u32
+				+ 587 * self.palette[4 * index + 1] as u32
+				+ 114 * self.palette[4 * index + 2] as u32;
+		};
+		
+		order[first..].sort_by_key(|index| luminance(*index));
+		
+		let mut new_palette: Vec<u8> = Vec::with_capacity(self.palette.len());
+		let mut index_map: Vec<i32> = vec![0; color_count];
+		
+		for (new_index, old_index) in order.iter().enumerate() {
+			new_palette.extend_from_slice(&self.palette.as_slice()[4 * old_index..4 * old_index + 4]);
+			index_map[*old_index] = new_index as i32;
+		}
+		
+		self.palette = PackedByteArray::from(new_palette);
+		return PackedInt32Array::from(index_map);
+	}
+	
+	
+	/// Finds colors that appear more than once. Returns a dictionary mapping the first index
+	/// of each repeated color to a [PackedInt64Array] of the later indices sharing it.
+	/// Colors are compared by RGB, or RGBA with `compare_alpha`.
+	#[func]
+	pub fn find_duplicates(&self, compare_alpha: bool) -> Dictionary {
+		let compared: usize = 3 + compare_alpha as usize;
+		let color_count: usize = self.palette.len() / 4;
+		let palette: &[u8] = self.palette.as_slice();
+		let mut canonical: Vec<Option<usize>> = vec![None; color_count];
+		let mut duplicates: Dictionary = Dictionary::new();
+		
+		for index in 0..color_count {
+			if canonical[index].is_some() {
+				continue;
+			}
+			
+			let color: &[u8] = &palette[4 * index..4 * index + compared];
+			let mut matches: Vec<i64> = Vec::new();
+			
+			for other in index + 1..color_count {
+				if canonical[other].is_none() && &palette[4 * other..4 * other + compared] == color {
+					canonical[other] = Some(index);
+					matches.push(other as i64);
+				}
+			}
+			
+			if !matches.is_empty() {
+				duplicates.set(index as i64, PackedInt64Array::from(matches));
+			}
+		}
+		
+		return duplicates;
+	}
+	
+	
+	/// Cycles the colors in the inclusive range [start, end] by `steps`, moving each color
+	/// to a higher index (negative steps move them lower). The range is clamped to the
+	/// palette; empty or inverted ranges do nothing.
+	#[func]
+	pub fn rotate_range(&mut self, start: u16, end: u16, steps: i64) {
+		let color_count: usize = self.palette.len() / 4;
+		
+		if color_count == 0 {
+			return;
+		}
+		
+		let start: usize = start as usize;
+		let end: usize = std::cmp::min(end as usize, color_count - 1);
+		
+		if start > end {
+			return;
+		}
+		
+		let length: usize = end - start + 1;
+		let shift: usize = steps.rem_euclid(length as i64) as usize;
+		
+		let mut palette: Vec<u8> = self.palette.to_vec();
+		palette[4 * start..4 * (end + 1)].rotate_right(4 * shift);
+		self.palette = PackedByteArray::from(palette);
+	}
+	
+	
 	/// Reindexing function. Reorders colors from 1-2-3-4 to 1-3-2-4 and vice-versa.
 	#[func]
 	pub fn reindex(&mut self) {		
@@ -322,4 +870,37 @@ impl BinPalette {
 	pub fn alpha_double(&mut self) {
 		self.palette = sprite_transform::alpha_double(self.palette.to_vec()).into();
 	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	#[test]
+	fn tiny_buffer_is_rejected() {
+		assert!(BinPalette::from_bin_data(vec![0x00, 0x00, 0x20]).is_none());
+	}
+	
+	
+	#[test]
+	fn truncated_palette_is_rejected() {
+		let mut bin_data: Vec<u8> = vec![0x00; 0x20];
+		bin_data[0x02] = 0x20;
+		bin_data[0x04] = 0x04;
+		assert!(BinPalette::from_bin_data(bin_data).is_none());
+	}
+	
+	
+	#[test]
+	fn palette_header_matches_color_count() {
+		let small: Vec<u8> = BinPalette::palette_to_bin(vec![0x00; 16 * 4]);
+		assert_eq!(small.len(), 0x50);
+		assert_eq!(small[0x04], 0x04);
+		
+		let large: Vec<u8> = BinPalette::palette_to_bin(vec![0x00; 256 * 4]);
+		assert_eq!(large.len(), 0x410);
+		assert_eq!(large[0x04], 0x08);
+	}
 }
\ No newline at end of file
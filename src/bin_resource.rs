@@ -8,10 +8,14 @@ use std::path::PathBuf;
 use godot::prelude::*;
 
 use crate::bin_identify::*;
+use crate::audio_vag;
+use crate::bin_sprite;
 use crate::bin_sprite::BinSprite;
+use crate::bin_cell;
 use crate::bin_cell::Cell;
 use crate::bin_palette::BinPalette;
 use crate::sprite_load_save::SpriteLoadSave;
+use crate::logging::*;
 
 /* object_type:
  *		"sprite"				<- single sprite
@@ -20,6 +24,8 @@ use crate::sprite_load_save::SpriteLoadSave;
  *		"jpf_plain_text"		<- char_index.bin, then individual sprites
  *		"scriptable"			<- cells, sprites, script, and possibly palettes
  *		"wii_tpl"				<- Wii TPL texture (not currently in use)
+ *		"palette"				<- single palette
+ *		"palette_file"			<- standalone palette file, no header pointers
  *		"multi_object"			<- contains scriptable subobjects (archive_jpf.bin effects)
  *		"dummy"					<- "DUMMY" padding (not currently in use)
  *		"unsupported"			<- as-is binary passthrough
@@ -40,6 +46,20 @@ struct Scriptable {
 /// Representation of a single binary resource file.
 struct BinResource {
 	base: Base<Resource>,
+	// Step-wise load state, see begin_load()
+	pending_objects: Vec<Vec<u8>>,
+	loaded_objects: Dictionary,
+	next_object: usize,
+	object_number: usize,
+	cancelled: bool,
+}
+
+
+// How a file's contents get loaded.
+enum FileKind {
+	PaletteFile,
+	SpriteListFile,
+	ResourceFile,
 }
 
 
@@ -48,6 +68,11 @@ impl IResource for BinResource {
 	fn init(base: Base<Resource>) -> Self {
 		Self {
 			base,
+			pending_objects: Vec::new(),
+			loaded_objects: Dictionary::new(),
+			next_object: 0,
+			object_number: 0,
+			cancelled: false,
 		}
 	}
 }
@@ -55,6 +80,11 @@ impl IResource for BinResource {
 
 #[godot_api]
 impl BinResource {
+	/// Emitted by load_next() as each object of a resource file is parsed.
+	#[signal]
+	fn object_loaded(index: i64, total: i64, object_type: GString);
+	
+	
 	/// Loads a BIN resource file, returning the objects contained within.
 	#[func] fn from_file(source_path: String) -> Dictionary {
 		let path_buf: PathBuf = PathBuf::from(&source_path);
@@ -62,7 +92,7 @@ impl BinResource {
 		//godot_print!("Loading {}...", &source_path);
 		
 		if !path_buf.exists() {
-			godot_print!("File was not found");
+			log_error!("File was not found");
 			return dict! {
 				"error": "File not found",
 			};
@@ -76,68 +106,184 @@ impl BinResource {
 			},
 		}
 	}
-
-
-	fn load_binary_data(bin_data: Vec<u8>) -> Dictionary {
+	
+	
+	/// Starts loading a BIN resource file one object at a time, so the caller can show
+	/// progress and stay responsive. Returns {"total": int}, the number of objects left
+	/// for load_next(), or {"error": String}. Standalone palette and sprite list files
+	/// load here in full, with a total of 0.
+	#[func] fn begin_load(&mut self, source_path: String) -> Dictionary {
+		self.reset_load();
+		
+		let path_buf: PathBuf = PathBuf::from(&source_path);
+		
+		if !path_buf.exists() {
+			log_error!("File was not found");
+			return dict! {
+				"error": "File not found",
+			};
+		}
+		
+		let bin_data: Vec<u8>;
+		
+		match fs::read(path_buf) {
+			Ok(data) => bin_data = data,
+			
+			_ => return dict! {
+				"error": "Could not read file",
+			},
+		}
+		
+		match Self::check_binary_data(&bin_data) {
+			Err(error) => return error,
+			
+			Ok(FileKind::ResourceFile) => {
+				self.pending_objects = Self::get_objects(&bin_data);
+			},
+			
+			Ok(_) => {
+				self.loaded_objects = Self::load_binary_data(bin_data);
+			},
+		}
+		
+		return dict! {
+			"total": self.pending_objects.len() as i64,
+		};
+	}
+	
+	
+	/// Loads the next object of the file opened with begin_load().
+	/// Returns true while objects remain, false once done or cancelled.
+	#[func] fn load_next(&mut self) -> bool {
+		if self.cancelled || self.next_object >= self.pending_objects.len() {
+			return false;
+		}
+		
+		let index: usize = self.next_object;
+		let dictionary: Dictionary = Self::load_object(&self.pending_objects[index], &mut self.object_number);
+		
+		let object_type: Variant = dictionary.at("type");
+		let total: usize = self.pending_objects.len();
+		
+		self.loaded_objects.set(index as u32, dictionary);
+		self.next_object += 1;
+		
+		self.base_mut().emit_signal("object_loaded", &[
+			(index as i64).to_variant(),
+			(total as i64).to_variant(),
+			object_type,
+		]);
+		
+		return self.next_object < self.pending_objects.len();
+	}
+	
+	
+	/// Stops the load started with begin_load(), keeping the objects loaded so far.
+	#[func] fn cancel_load(&mut self) {
+		if self.next_object < self.pending_objects.len() {
+			log_warn!("Loading cancelled after {} of {} objects", self.next_object, self.pending_objects.len());
+			self.cancelled = true;
+		}
+	}
+	
+	
+	/// Ends the load started with begin_load(), returning the objects as from_file() would.
+	/// A cancelled or unfinished load instead returns {"data": objects, "cancelled": true}.
+	/// Cancelled results cannot be saved.
+	#[func] fn finish_load(&mut self) -> Dictionary {
+		let result: Dictionary;
+		
+		if self.cancelled || self.next_object < self.pending_objects.len() {
+			result = dict! {
+				"data": self.loaded_objects.clone(),
+				"cancelled": true,
+			};
+		} else {
+			result = self.loaded_objects.clone();
+		}
+		
+		self.reset_load();
+		return result;
+	}
+	
+	
+	fn reset_load(&mut self) {
+		self.pending_objects = Vec::new();
+		self.loaded_objects = Dictionary::new();
+		self.next_object = 0;
+		self.object_number = 0;
+		self.cancelled = false;
+	}
+	
+	
+	// Rejects files that can't be loaded, otherwise works out how to load them.
+	fn check_binary_data(bin_data: &Vec<u8>) -> Result<FileKind, Dictionary> {
 		let data_length: usize = bin_data.len();
 		
 		// Smallest possible file is a SpriteList with a single, palette-less 1x1 sprite
 		// Such a file is 48 bytes long (0x30 hex)
 		if data_length < 0x30 {
-			return dict! {
+			return Err(dict! {
 				"error": "Invalid file (too short)",
-			}
+			});
 		}
 		
 		if u32::from_le_bytes([
 			bin_data[data_length - 0x01], bin_data[data_length - 0x02],
 			bin_data[data_length - 0x03], bin_data[data_length - 0x04],
 		]) == ENCRYPTED_SIGNATURE {
-			return dict! {
+			return Err(dict! {
 				"error": "Invalid file (encrypted)"
-			}
+			});
+		}
+		
+		// Standalone palette files have no header pointers
+		if identify_palette(&bin_data) {
+			return Ok(FileKind::PaletteFile);
 		}
 		
 		// Check if it's a spritelist first.
-		let mut sprite_list: bool = true;
 		let objects: Vec<Vec<u8>> = Self::get_objects(&bin_data);
 		
 		if objects.len() == 0 {
-			return dict! {
+			return Err(dict! {
 				"error": "Invalid file (no objects)"
-			}
+			});
 		}
 		
 		for object in 0..objects.len() {
 			match identify_object(&objects[object]) {
 				ObjectType::Sprite => continue,
-				
-				_ => {
-					sprite_list = false;
-					break;
-				},
+				_ => return Ok(FileKind::ResourceFile),
 			}
 		}
 		
-		
-		if sprite_list {
-			return Self::load_sprite_list_file(bin_data);
-		} else {
-			return Self::load_resource_file(bin_data);
+		return Ok(FileKind::SpriteListFile);
+	}
+
+
+	fn load_binary_data(bin_data: Vec<u8>) -> Dictionary {
+		match Self::check_binary_data(&bin_data) {
+			Err(error) => return error,
+			Ok(FileKind::PaletteFile) => return Self::load_palette_file(bin_data),
+			Ok(FileKind::SpriteListFile) => return Self::load_sprite_list_file(bin_data),
+			Ok(FileKind::ResourceFile) => return Self::load_resource_file(bin_data),
 		}
 	}
 	
 	
 	/// Loads a parsed resource from a directory, returning the objects contained within.
-	#[func] fn from_path(source_path: String) -> Dictionary {
+	/// Objects are ordered by the directory's order.json if present, otherwise by name.
+	/// Without an order.json, `player_first` moves the "player" object to the front.
+	#[func] fn from_path(source_path: String, player_first: bool) -> Dictionary {
 		let path_buf: PathBuf = PathBuf::from(&source_path);
 		
-		godot_print!("Loading {}...", &source_path);
+		log_info!("Loading {}...", &source_path);
 		
 		let mut resource_dictionary: Dictionary = Dictionary::new();
 		
 		if !path_buf.exists() {
-			godot_print!("Path was not found");
+			log_error!("Path was not found");
 			return dict! {
 				"error": "Path not found",
 			};
@@ -163,30 +309,66 @@ impl BinResource {
 			
 			// We only get here if a directory was correctly read from the source_path
 			match Self::load_object_directory(entry.path()) {
-				Some(dictionary) => {
-					let name: String = dictionary.at("name").to();
-					
-					if name == "player" {
-						object_vector.insert(0, dictionary);
-					}
-					
-					else {
-						object_vector.push(dictionary);
-					}
-				},
-				
+				Some(dictionary) => object_vector.push(dictionary),
 				_ => (),
 			}
 		}
-			
-		for object in 0..object_vector.len() {
-			resource_dictionary.set(object as i64, object_vector[object].clone());
+		
+		let names: Vec<String> = object_vector.iter().map(|object| object.at("name").to::<String>()).collect();
+		let order: Vec<usize> = Self::order_object_names(&names, &Self::load_object_order(&path_buf), player_first);
+		
+		for object in 0..order.len() {
+			resource_dictionary.set(object as i64, object_vector[order[object]].clone());
 		}
 		
 		return resource_dictionary;
 	}
 	
 	
+	// Returns the indices of `names` in load order. See from_path().
+	fn order_object_names(names: &Vec<String>, order: &Option<Vec<String>>, player_first: bool) -> Vec<usize> {
+		let mut indices: Vec<usize> = (0..names.len()).collect();
+		
+		// read_dir() order is platform dependent, start from a stable one
+		indices.sort_by(|a, b| natord::compare(&names[*a], &names[*b]));
+		
+		match order {
+			// Listed objects first, in order; unlisted ones keep their relative order
+			Some(order) => indices.sort_by_key(|index| {
+				order.iter().position(|item| *item == names[*index]).unwrap_or(order.len())
+			}),
+			
+			None => if player_first {
+				indices.sort_by_key(|index| names[*index] != "player");
+			},
+		}
+		
+		return indices;
+	}
+	
+	
+	fn load_object_order(path_buf: &PathBuf) -> Option<Vec<String>> {
+		let order_path: PathBuf = Path::new(path_buf).join("order.json");
+		
+		if !order_path.exists() {
+			return None;
+		}
+		
+		match fs::read_to_string(order_path) {
+			Ok(string) => match serde_json::from_str::<Vec<String>>(&string) {
+				Ok(order) => return Some(order),
+				
+				_ => {
+					log_error!("bin_resource::load_object_order(): Could not parse order.json!");
+					return None;
+				},
+			},
+			
+			_ => return None,
+		}
+	}
+	
+	
 	fn load_object_directory(path_buf: PathBuf) -> Option<Dictionary> {
 		let mut object_dictionary: Dictionary = Dictionary::new();
 
@@ -216,11 +398,22 @@ impl BinResource {
 		let mut cell_array_len: usize = 0usize;
 		
 		if cell_path.exists() {
-			for item in Self::get_file_vector(cell_path) {
-				match Cell::from_file(item) {
-					Some(cell) => cell_array.push(&cell),
-					_ => (),
-				}
+			// Single array file takes priority over individual cell files
+			match Cell::from_array_file(Path::new(&cell_path).join("cells.json")) {
+				Some(cells) => {
+					for cell in cells {
+						cell_array.push(&cell);
+					}
+				},
+				
+				None => {
+					for item in Self::get_file_vector(cell_path) {
+						match Cell::from_file(item) {
+							Some(cell) => cell_array.push(&cell),
+							_ => (),
+						}
+					}
+				},
 			}
 			
 			// We need this again below
@@ -313,24 +506,36 @@ impl BinResource {
 
 	
 	fn get_objects(bin_data: &Vec<u8>) -> Vec<Vec<u8>> {
-		let header_pointers: Vec<usize> = get_pointers(&bin_data, 0x00, false);
 		let mut objects: Vec<Vec<u8>> = Vec::new();
 		
+		for (start, end) in Self::get_object_ranges(bin_data) {
+			objects.push(bin_data[start..end].to_vec());
+		}
+		
+		return objects;
+	}
+	
+	
+	// Start and end offsets of each object, from the header pointers alone.
+	fn get_object_ranges(bin_data: &Vec<u8>) -> Vec<(usize, usize)> {
+		let header_pointers: Vec<usize> = get_pointers_validated(&bin_data, 0x00, false);
+		let mut ranges: Vec<(usize, usize)> = Vec::new();
+		
 		for pointer in 0..header_pointers.len() {
 			if header_pointers[pointer] >= bin_data.len() {
-				return objects;
+				return ranges;
 			}
 			
 			if pointer == header_pointers.len() - 1 {
-				objects.push(bin_data[header_pointers[pointer]..].to_vec());
+				ranges.push((header_pointers[pointer], bin_data.len()));
 			}
 			
 			else {
-				objects.push(bin_data[header_pointers[pointer]..header_pointers[pointer + 1]].to_vec());
+				ranges.push((header_pointers[pointer], header_pointers[pointer + 1]));
 			}
 		}
 		
-		return objects;
+		return ranges;
 	}
 	
 	
@@ -341,156 +546,184 @@ impl BinResource {
 		// For every sub object
 		let mut object_number: usize = 0;
 		for object in 0..objects.len() {
-			let object_bin_data: &Vec<u8> = &objects[object];
-			let mut dictionary: Dictionary;
-			
-			// Get and load per object type
-			match identify_object(&object_bin_data) {
+			let dictionary: Dictionary = Self::load_object(&objects[object], &mut object_number);
+			resource_dictionary.set(object as u32, dictionary);
+		}
+
+		return resource_dictionary;
+	}
+	
+	
+	// Parses a single object. `object_number` names unnamed scriptables and is advanced past them.
+	fn load_object(object_bin_data: &Vec<u8>, object_number: &mut usize) -> Dictionary {
+		let mut dictionary: Dictionary;
+		
+		// Get and load per object type
+		match identify_object(&object_bin_data) {
+		
+		
+			ObjectType::Sprite => {
+				let sprite = SpriteLoadSave::load_sprite_data(object_bin_data);
+				let mut array: Array<Gd<BinSprite>> = Array::new();
+				
+				match sprite {
+					Some(bin_sprite) => {
+						array.push(&bin_sprite);
+						
+						dictionary = dict! {
+							"type": "sprite",
+							"sprites": array,
+						}
+					},
+					
+					None => {
+						dictionary = dict! {
+							"type": "unsupported",
+							"data": PackedByteArray::from(object_bin_data.clone()),
+						}
+					},
+				}
+			}
 			
 			
-				ObjectType::Sprite => {
-					let sprite = SpriteLoadSave::load_sprite_data(object_bin_data);
-					let mut array: Array<Gd<BinSprite>> = Array::new();
-					
-					match sprite {
-						Some(bin_sprite) => {
-							array.push(&bin_sprite);
-							
-							dictionary = dict! {
-								"type": "sprite",
-								"sprites": array,
-							}
-						},
+			ObjectType::Palette => {
+				match BinPalette::from_bin_data(object_bin_data.clone()) {
+					Some(bin_palette) => {
+						let mut array: Array<Gd<BinPalette>> = Array::new();
+						array.push(&bin_palette);
 						
-						None => {
-							dictionary = dict! {
-								"type": "unsupported",
-								"data": PackedByteArray::from(object_bin_data.clone()),
-							}
-						},
-					}
+						dictionary = dict! {
+							"type": "palette",
+							"palettes": array,
+						}
+					},
+					
+					None => {
+						dictionary = dict! {
+							"type": "unsupported",
+							"data": PackedByteArray::from(object_bin_data.clone()),
+						}
+					},
 				}
+			},
+			
+			
+			ObjectType::SpriteListSelect => {
+				let pointers: Vec<usize> = get_pointers(object_bin_data, 0x00, false);
+				let last_pointer = pointers[pointers.len() - 1];
+				let mut sprites = Self::load_sprite_list(object_bin_data, 0);
+				let _ = sprites.pop();
 				
+				let select_w = u32::from_le_bytes([
+					object_bin_data[last_pointer + 0x00],
+					object_bin_data[last_pointer + 0x01],
+					object_bin_data[last_pointer + 0x02],
+					object_bin_data[last_pointer + 0x03],
+				]);
 				
-				ObjectType::SpriteListSelect => {
-					let pointers: Vec<usize> = get_pointers(object_bin_data, 0x00, false);
-					let last_pointer = pointers[pointers.len() - 1];
-					let mut sprites = Self::load_sprite_list(object_bin_data, 0);
-					let _ = sprites.pop();
-					
-					let select_w = u32::from_le_bytes([
-						object_bin_data[last_pointer + 0x00],
-						object_bin_data[last_pointer + 0x01],
-						object_bin_data[last_pointer + 0x02],
-						object_bin_data[last_pointer + 0x03],
-					]);
-					
-					let select_h = u32::from_le_bytes([
-						object_bin_data[last_pointer + 0x04],
-						object_bin_data[last_pointer + 0x05],
-						object_bin_data[last_pointer + 0x06],
-						object_bin_data[last_pointer + 0x07],
-					]);
-					
-					let select_pixels = PackedByteArray::from(
-						object_bin_data[last_pointer + 0x08..last_pointer + 0x08 + (select_w * select_h) as usize].to_vec());
-					
-					dictionary = dict! {
-						"type": "sprite_list_select",
-						"sprites": sprites,
-						"select_width": select_w,
-						"select_height": select_h,
-						"select_pixels": select_pixels,
-					};
-				},
+				let select_h = u32::from_le_bytes([
+					object_bin_data[last_pointer + 0x04],
+					object_bin_data[last_pointer + 0x05],
+					object_bin_data[last_pointer + 0x06],
+					object_bin_data[last_pointer + 0x07],
+				]);
 				
+				let select_pixels = PackedByteArray::from(
+					object_bin_data[last_pointer + 0x08..last_pointer + 0x08 + (select_w * select_h) as usize].to_vec());
 				
-				ObjectType::SpriteList => {
-					let sprites = Self::load_sprite_list(object_bin_data, 0);
-					
-					dictionary = dict! {
-						"type": "sprite_list",
-						"sprites": sprites,
-					};
-				},
+				dictionary = dict! {
+					"type": "sprite_list_select",
+					"sprites": sprites,
+					"select_width": select_w,
+					"select_height": select_h,
+					"select_pixels": select_pixels,
+				};
+			},
+			
+			
+			ObjectType::SpriteList => {
+				let sprites = Self::load_sprite_list(object_bin_data, 0);
 				
+				dictionary = dict! {
+					"type": "sprite_list",
+					"sprites": sprites,
+				};
+			},
+			
+			
+			ObjectType::JPFPlainText => {
+				let pointers: Vec<usize> = get_pointers(object_bin_data, 0x00, false);
 				
-				ObjectType::JPFPlainText => {
-					let pointers: Vec<usize> = get_pointers(object_bin_data, 0x00, false);
-					
-					let char_index = PackedByteArray::from(
-						object_bin_data[pointers[0]..pointers[1]].to_vec()
-					);
-					
-					let sprites: Array<Gd<BinSprite>> = Self::load_sprite_list(object_bin_data, 1);
-					
-					dictionary = dict! {
-						"type": "jpf_plain_text",
-						"char_index": char_index,
-						"sprites": sprites,
-					}
-				},
+				let char_index = PackedByteArray::from(
+					object_bin_data[pointers[0]..pointers[1]].to_vec()
+				);
 				
+				let sprites: Array<Gd<BinSprite>> = Self::load_sprite_list(object_bin_data, 1);
 				
-				ObjectType::Scriptable => {
-					let scriptable: Scriptable = Self::load_scriptable(object_bin_data, object_number);
-					
-					if scriptable.name != "Player" {
-						object_number += 1;
-					}
-					
-					dictionary = dict! {
+				dictionary = dict! {
+					"type": "jpf_plain_text",
+					"char_index": char_index,
+					"sprites": sprites,
+				}
+			},
+			
+			
+			ObjectType::Scriptable => {
+				let scriptable: Scriptable = Self::load_scriptable(object_bin_data, *object_number);
+				
+				if scriptable.name != "Player" {
+					*object_number += 1;
+				}
+				
+				dictionary = dict! {
+					"type": "scriptable",
+					"name": scriptable.name,
+					"cells": scriptable.cells,
+					"sprites": scriptable.sprites,
+					"scripts": scriptable.scripts,
+				};
+				
+				if scriptable.palettes.len() > 0 {
+					dictionary.set("palettes", scriptable.palettes);
+				}
+			},
+			
+			
+			// Only used by archive_jpf.bin, for speed,
+			// assume rather than try to ID each object
+			ObjectType::MultiScriptable => {
+				let mut multi_scriptable: Dictionary = dict! {};
+				let scriptables: Vec<Vec<u8>> = Self::get_objects(object_bin_data);
+				
+				for item in 0..scriptables.len() {
+					let scriptable: Scriptable = Self::load_scriptable(&scriptables[item], item);
+					let scriptable_dict: Dictionary = dict! {
+						"name": "Effect",
 						"type": "scriptable",
-						"name": scriptable.name,
 						"cells": scriptable.cells,
 						"sprites": scriptable.sprites,
 						"scripts": scriptable.scripts,
 					};
 					
-					if scriptable.palettes.len() > 0 {
-						dictionary.set("palettes", scriptable.palettes);
-					}
-				},
-				
-				
-				// Only used by archive_jpf.bin, for speed,
-				// assume rather than try to ID each object
-				ObjectType::MultiScriptable => {
-					let mut multi_scriptable: Dictionary = dict! {};
-					let scriptables: Vec<Vec<u8>> = Self::get_objects(object_bin_data);
-					
-					for item in 0..scriptables.len() {
-						let scriptable: Scriptable = Self::load_scriptable(&scriptables[item], item);
-						let scriptable_dict: Dictionary = dict! {
-							"name": "Effect",
-							"type": "scriptable",
-							"cells": scriptable.cells,
-							"sprites": scriptable.sprites,
-							"scripts": scriptable.scripts,
-						};
-						
-						multi_scriptable.set(item as i64, scriptable_dict);
-					}
-				
-					dictionary = dict! {
-						"type": "multi_scriptable",
-						"data": multi_scriptable,
-					};
-				},
-				
-				
-				_ => {
-					dictionary = dict! {
-						"type": "unsupported",
-						"data": PackedByteArray::from(object_bin_data.clone()),
-					};
-				},
-			}
-
-			resource_dictionary.set(object as u32, dictionary);
+					multi_scriptable.set(item as i64, scriptable_dict);
+				}
+			
+				dictionary = dict! {
+					"type": "multi_scriptable",
+					"data": multi_scriptable,
+				};
+			},
+			
+			
+			_ => {
+				dictionary = dict! {
+					"type": "unsupported",
+					"data": PackedByteArray::from(object_bin_data.clone()),
+				};
+			},
 		}
-
-		return resource_dictionary;
+		
+		return dictionary;
 	}
 	
 	
@@ -511,7 +744,7 @@ impl BinResource {
 			
 			match SpriteLoadSave::load_sprite_data(&sprite_bin_data) {
 				Some(bin_sprite) => sprites.push(&bin_sprite),
-				_ => sprites.push(&BinSprite::new_gd()),
+				_ => sprites.push(&Self::unreadable_sprite(sprite_bin_data)),
 			}
 		}
 		
@@ -524,6 +757,25 @@ impl BinResource {
 	}
 	
 	
+	fn load_palette_file(bin_data: Vec<u8>) -> Dictionary {
+		let mut palettes: Array<Gd<BinPalette>> = Array::new();
+		
+		match BinPalette::from_bin_data(bin_data) {
+			Some(bin_palette) => palettes.push(&bin_palette),
+			None => return dict! {
+				"error": "Invalid file (bad palette)",
+			},
+		}
+		
+		return dict! {
+			0u32: dict! {
+				"type": "palette_file",
+				"palettes": palettes,
+			}
+		}
+	}
+	
+	
 	// =================================================================================
 	// OBJECT LOADING
 	// =================================================================================
@@ -559,6 +811,13 @@ impl BinResource {
 
 		for cell in cell_pointers.iter() {
 			let cursor: usize = pointers[0] + cell;
+			
+			if cursor + 0x04 > bin_data.len() {
+				log_error!("BinResource::load_cells() error: Cell at 0x{:X} out of bounds", cursor);
+				cells.push(&Cell::new_gd());
+				continue;
+			}
+			
 			let hitbox_count: u32 = u32::from_le_bytes([
 				bin_data[cursor + 0x00],
 				bin_data[cursor + 0x01],
@@ -566,7 +825,21 @@ impl BinResource {
 				bin_data[cursor + 0x03]
 			]);
 			
-			let cell_slice: &[u8] = &bin_data[cursor..cursor + 0x10 + (hitbox_count as usize * 0x0C)];
+			if hitbox_count > bin_cell::MAX_HITBOX_COUNT {
+				log_error!("BinResource::load_cells() error: Cell at 0x{:X} has {} hitboxes", cursor, hitbox_count);
+				cells.push(&Cell::new_gd());
+				continue;
+			}
+			
+			let cell_end: usize = cursor + 0x10 + (hitbox_count as usize * 0x0C);
+			
+			if cell_end > bin_data.len() {
+				log_error!("BinResource::load_cells() error: Cell at 0x{:X} out of bounds", cursor);
+				cells.push(&Cell::new_gd());
+				continue;
+			}
+			
+			let cell_slice: &[u8] = &bin_data[cursor..cell_end];
 			match Cell::from_binary_data(cell_slice) {
 				Some(cell) => cells.push(&cell),
 				_ => cells.push(&Cell::new_gd()),
@@ -594,13 +867,15 @@ impl BinResource {
 				end = pointers[2];
 			}
 			
-			match SpriteLoadSave::load_sprite_data(&bin_data[start..end].to_vec()) {
+			let sprite_data: Vec<u8> = bin_data[start..end].to_vec();
+			
+			match SpriteLoadSave::load_sprite_data(&sprite_data) {
 				Some(sprite) => {
 					sprites.push(&sprite);
 				},
 				
 				None => {
-					sprites.push(&BinSprite::new_gd());
+					sprites.push(&Self::unreadable_sprite(sprite_data));
 				},
 			}
 		}
@@ -635,7 +910,14 @@ impl BinResource {
 		let palette_pointers: Vec<usize> = get_pointers(&bin_data, pointers[3], false);
 		for palette in palette_pointers.iter() {
 			let cursor: usize = pointers[3] + palette;
-			let palette_data: Vec<u8> = bin_data[cursor..cursor + 0x410].to_vec();
+			let palette_end: usize = std::cmp::min(cursor + 0x410, bin_data.len());
+			
+			if cursor >= palette_end {
+				palettes.push(&BinPalette::new_gd());
+				continue;
+			}
+			
+			let palette_data: Vec<u8> = bin_data[cursor..palette_end].to_vec();
 			
 			match BinPalette::from_bin_data(palette_data) {
 				Some(palette) => palettes.push(&palette),
@@ -669,40 +951,567 @@ impl BinResource {
 					sprites.push(&sprite);
 				},
 				
-				None => {
-					sprites.push(&BinSprite::new_gd());
+				None => {
+					sprites.push(&Self::unreadable_sprite(sprite_data));
+				}
+			}
+		}
+		
+		return sprites;
+	}
+	
+	
+	// Blank stand-in for a sprite that failed to load. Keeps the original bytes so an
+	// untouched object saves back unchanged instead of with a placeholder.
+	fn unreadable_sprite(sprite_data: Vec<u8>) -> Gd<BinSprite> {
+		let mut sprite: Gd<BinSprite> = BinSprite::new_gd();
+		sprite.bind_mut().set_source_bin(sprite_data);
+		return sprite;
+	}
+
+
+	// =================================================================================
+	// QUERIES
+	// =================================================================================
+	
+	
+	/// Returns {sprites, cells, palettes, scripts_bytes} summed over every object in
+	/// a loaded resource, including the sub-objects of "multi_scriptable" objects.
+	#[func] fn session_totals(dictionary: Dictionary) -> Dictionary {
+		let mut totals: [i64; 4] = [0; 4];
+		
+		for (_key, value) in dictionary.iter_shared() {
+			match value.try_to::<Dictionary>() {
+				Ok(object_dict) => Self::add_object_totals(&object_dict, &mut totals),
+				_ => continue,
+			}
+		}
+		
+		return dict! {
+			"sprites": totals[0],
+			"cells": totals[1],
+			"palettes": totals[2],
+			"scripts_bytes": totals[3],
+		};
+	}
+	
+	
+	// Adds an object's sprite, cell, palette and script byte counts to `totals`.
+	fn add_object_totals(object_dict: &Dictionary, totals: &mut [i64; 4]) {
+		match object_dict.get("sprites") {
+			Some(value) => totals[0] += value.to::<Array<Gd<BinSprite>>>().len() as i64,
+			None => (),
+		}
+		
+		match object_dict.get("cells") {
+			Some(value) => totals[1] += value.to::<Array<Gd<Cell>>>().len() as i64,
+			None => (),
+		}
+		
+		match object_dict.get("palettes") {
+			Some(value) => totals[2] += value.to::<Array<Gd<BinPalette>>>().len() as i64,
+			None => (),
+		}
+		
+		match object_dict.get("scripts") {
+			Some(value) => totals[3] += value.to::<PackedByteArray>().len() as i64,
+			None => (),
+		}
+		
+		// multi_scriptable
+		if object_dict.get("type").map_or(false, |object_type| object_type.to_string() == "multi_scriptable") {
+			let inner_dict: Dictionary = object_dict.at("data").to();
+			
+			for (_key, value) in inner_dict.iter_shared() {
+				Self::add_object_totals(&value.to::<Dictionary>(), totals);
+			}
+		}
+	}
+	
+	
+	/// Returns a hash covering an object's sprites, cells, scripts, palettes and raw data.
+	/// Stable across sessions, so it can key cached previews.
+	#[func] fn object_content_hash(object_dict: Dictionary) -> i64 {
+		return Self::object_hash(bin_sprite::FNV_OFFSET, &object_dict) as i64;
+	}
+	
+	
+	fn object_hash(mut hash: u64, object_dict: &Dictionary) -> u64 {
+		let object_type: String = object_dict.get("type").map_or(String::new(), |value| value.to_string());
+		let mut sprite_hashes: Vec<u64> = Vec::new();
+		let mut cell_bins: Vec<Vec<u8>> = Vec::new();
+		let mut byte_fields: Vec<Vec<u8>> = Vec::new();
+		let mut palettes: Vec<Vec<u8>> = Vec::new();
+		
+		match object_dict.get("sprites") {
+			Some(value) => {
+				for sprite in value.to::<Array<Gd<BinSprite>>>().iter_shared() {
+					sprite_hashes.push(sprite.bind().content_hash());
+				}
+			},
+			
+			None => (),
+		}
+		
+		match object_dict.get("cells") {
+			Some(value) => {
+				for cell in value.to::<Array<Gd<Cell>>>().iter_shared() {
+					cell_bins.push(cell.bind().to_bin());
+				}
+			},
+			
+			None => (),
+		}
+		
+		for key in ["scripts", "select_pixels", "char_index"] {
+			match object_dict.get(key) {
+				Some(value) => byte_fields.push(value.to::<PackedByteArray>().to_vec()),
+				None => (),
+			}
+		}
+		
+		match object_dict.get("palettes") {
+			Some(value) => {
+				for palette in value.to::<Array<Gd<BinPalette>>>().iter_shared() {
+					palettes.push(palette.bind().palette.to_vec());
+				}
+			},
+			
+			None => (),
+		}
+		
+		hash = Self::hash_object_parts(hash, &object_type, &sprite_hashes, &cell_bins, &byte_fields, &palettes);
+		
+		match object_dict.get("data") {
+			Some(value) => {
+				// multi_scriptable holds objects, unsupported holds raw bytes
+				match value.try_to::<Dictionary>() {
+					Ok(inner_dict) => {
+						for (_key, inner_value) in inner_dict.iter_shared() {
+							hash = Self::object_hash(hash, &inner_value.to::<Dictionary>());
+						}
+					},
+					
+					_ => hash = bin_sprite::fnv_hash(hash, value.to::<PackedByteArray>().as_slice()),
+				}
+			},
+			
+			None => (),
+		}
+		
+		return hash;
+	}
+	
+	
+	// Hashes the parts of an object that object_hash() pulls out of its dictionary, in order.
+	fn hash_object_parts(
+		mut hash: u64, object_type: &str, sprite_hashes: &[u64], cell_bins: &[Vec<u8>],
+		byte_fields: &[Vec<u8>], palettes: &[Vec<u8>]
+	) -> u64 {
+		hash = bin_sprite::fnv_hash(hash, object_type.as_bytes());
+		
+		for sprite_hash in sprite_hashes {
+			hash = bin_sprite::fnv_hash(hash, &sprite_hash.to_le_bytes());
+		}
+		
+		for cell_bin in cell_bins {
+			hash = bin_sprite::fnv_hash(hash, cell_bin);
+		}
+		
+		for byte_field in byte_fields {
+			hash = bin_sprite::fnv_hash(hash, byte_field);
+		}
+		
+		for palette in palettes {
+			hash = bin_sprite::fnv_hash(hash, palette);
+		}
+		
+		return hash;
+	}
+	
+	
+	/// Lists the objects in a loaded resource that were not recognized, as
+	/// {index, length, head} entries. "head" holds the first 16 bytes as hex.
+	#[func] fn unsupported_report(dictionary: Dictionary) -> Array<Dictionary> {
+		let mut report: Array<Dictionary> = Array::new();
+		
+		for (key, value) in dictionary.iter_shared() {
+			let object_dict: Dictionary;
+			
+			match value.try_to::<Dictionary>() {
+				Ok(inner) => object_dict = inner,
+				_ => continue,
+			}
+			
+			match object_dict.get("type") {
+				Some(object_type) if object_type.to_string() == "unsupported" => (),
+				_ => continue,
+			}
+			
+			let data: PackedByteArray = object_dict.get("data").map_or(PackedByteArray::new(), |data| data.to());
+			let head: Vec<String> = data.as_slice()
+				.iter()
+				.take(0x10)
+				.map(|byte| format!("{:02X}", byte))
+				.collect();
+			
+			report.push(&dict! {
+				"index": key,
+				"length": data.len() as i64,
+				"head": head.join(" "),
+			});
+		}
+		
+		return report;
+	}
+	
+	
+	/// Returns the absolute file offset of each object in a BIN resource file.
+	/// Pointers that are out of bounds or not increasing end the list early, with a warning.
+	/// Returns an empty array if the file cannot be read.
+	#[func] fn object_offsets(path: String) -> PackedInt64Array {
+		let bin_data: Vec<u8>;
+		
+		match fs::read(PathBuf::from(&path)) {
+			Ok(data) => bin_data = data,
+			_ => {
+				log_error!("BinResource::object_offsets() error: Could not read {}", path);
+				return PackedInt64Array::new();
+			},
+		}
+		
+		let offsets: Vec<i64> = get_pointers_validated(&bin_data, 0x00, false)
+			.iter()
+			.map(|pointer| *pointer as i64)
+			.collect();
+		
+		return PackedInt64Array::from(offsets);
+	}
+	
+	
+	/// Loads a BIN resource file and re-serializes it in memory through the save path,
+	/// without writing anything. Returns {match, original_size, saved_size, first_mismatch,
+	/// objects}, where "objects" lists each object whose bytes changed as {index, offset,
+	/// original_length, saved_length, first_mismatch}. Mismatch positions are -1 when the
+	/// bytes agree up to the shorter length; object positions are relative to the object.
+	/// Returns {"error": String} if the file cannot be loaded.
+	#[func] fn verify_round_trip(source_path: String) -> Dictionary {
+		let original: Vec<u8>;
+		
+		match fs::read(PathBuf::from(&source_path)) {
+			Ok(data) => original = data,
+			_ => return dict! {
+				"error": "Could not read file",
+			},
+		}
+		
+		let loaded: Dictionary = Self::load_binary_data(original.clone());
+		
+		if loaded.contains_key("error") {
+			return loaded;
+		}
+		
+		let saved: Vec<u8> = Self::serialize_resource(&loaded, &mut None);
+		
+		let original_objects: Vec<Vec<u8>> = Self::get_objects(&original);
+		let saved_objects: Vec<Vec<u8>> = Self::get_objects(&saved);
+		let offsets: Vec<usize> = get_pointers_validated(&original, 0x00, false);
+		let mut objects: Array<Dictionary> = Array::new();
+		
+		for index in 0..std::cmp::max(original_objects.len(), saved_objects.len()) {
+			let empty: Vec<u8> = Vec::new();
+			let original_object: &Vec<u8> = original_objects.get(index).unwrap_or(&empty);
+			let saved_object: &Vec<u8> = saved_objects.get(index).unwrap_or(&empty);
+			
+			if original_object == saved_object {
+				continue;
+			}
+			
+			objects.push(&dict! {
+				"index": index as i64,
+				"offset": offsets.get(index).map_or(-1, |offset| *offset as i64),
+				"original_length": original_object.len() as i64,
+				"saved_length": saved_object.len() as i64,
+				"first_mismatch": Self::first_mismatch(original_object, saved_object),
+			});
+		}
+		
+		let matches: bool = original == saved;
+		
+		if !matches {
+			log_warn!("Round trip changed {}: {} object(s) differ", source_path, objects.len());
+		}
+		
+		return dict! {
+			"match": matches,
+			"original_size": original.len() as i64,
+			"saved_size": saved.len() as i64,
+			"first_mismatch": Self::first_mismatch(&original, &saved),
+			"objects": objects,
+		};
+	}
+	
+	
+	// Index of the first differing byte, or -1 if one is a prefix of the other.
+	fn first_mismatch(a: &[u8], b: &[u8]) -> i64 {
+		return a.iter().zip(b.iter()).position(|(x, y)| x != y).map_or(-1, |index| index as i64);
+	}
+	
+	
+	/// Returns how many VAGp audio streams an "unsupported" object's data points to.
+	/// Returns 0 if the data is not VAGp audio.
+	#[func] fn vagp_stream_count(data: PackedByteArray) -> i64 {
+		return Self::vagp_streams(&data.to_vec()).len() as i64;
+	}
+	
+	
+	/// Decodes VAGp stream number `stream` from an "unsupported" object's data and
+	/// saves it as a 16-bit mono .wav file. Returns true on success.
+	#[func] fn save_vagp_wav(data: PackedByteArray, stream: i64, path: String) -> bool {
+		let bin_data: Vec<u8> = data.to_vec();
+		let streams: Vec<usize> = Self::vagp_streams(&bin_data);
+		
+		if stream < 0 || stream as usize >= streams.len() {
+			log_error!("BinResource::save_vagp_wav() error: No VAGp stream # {}", stream);
+			return false;
+		}
+		
+		let (sample_rate, samples): (u32, Vec<i16>);
+		
+		match audio_vag::decode_vag(&bin_data[streams[stream as usize]..]) {
+			Some(decoded) => (sample_rate, samples) = decoded,
+			None => {
+				log_error!("BinResource::save_vagp_wav() error: Could not decode VAGp stream # {}", stream);
+				return false;
+			},
+		}
+		
+		match fs::File::create(PathBuf::from(&path)) {
+			Ok(file) => {
+				let ref mut buffer = BufWriter::new(file);
+				let _ = buffer.write_all(&audio_vag::make_wav(sample_rate, &samples));
+				let _ = buffer.flush();
+				return true;
+			},
+			
+			_ => {
+				log_error!("Could not create .WAV file!");
+				return false;
+			},
+		}
+	}
+	
+	
+	// Offsets of the VAGp streams listed in an audio object's big endian pointer table.
+	fn vagp_streams(bin_data: &Vec<u8>) -> Vec<usize> {
+		return get_pointers(bin_data, 0x00, true)
+			.into_iter()
+			.filter(|pointer| bin_data.len() >= *pointer + 4 && &bin_data[*pointer..*pointer + 4] == b"VAGp")
+			.collect();
+	}
+	
+	
+	/// Returns a summary of the sprites in a "sprite_list_file" or "sprite_list" object:
+	/// {count, blank, max_width, max_height, total_pixels, depths: {4: n, 8: m}}.
+	/// Blank sprites (failed loads) are counted in "count" and "blank" only.
+	#[func] fn sprite_list_stats(dictionary: Dictionary) -> Dictionary {
+		let sprite_array: Array<Gd<BinSprite>>;
+		
+		match dictionary.get("sprites") {
+			Some(value) => sprite_array = value.to(),
+			None => return dict! {
+				"error": "Object has no sprites",
+			},
+		}
+		
+		let mut blank: i64 = 0;
+		let mut max_width: i64 = 0;
+		let mut max_height: i64 = 0;
+		let mut total_pixels: i64 = 0;
+		let mut depths: Dictionary = Dictionary::new();
+		
+		for sprite in sprite_array.iter_shared() {
+			let binding = sprite.bind();
+			
+			match &binding.image {
+				Some(image) => {
+					let width: i64 = image.get_width() as i64;
+					let height: i64 = image.get_height() as i64;
+					
+					max_width = std::cmp::max(max_width, width);
+					max_height = std::cmp::max(max_height, height);
+					total_pixels += width * height;
+					
+					let depth: i64 = binding.bit_depth as i64;
+					let depth_count: i64 = depths.get(depth).map_or(0, |count| count.to());
+					depths.set(depth, depth_count + 1);
+				},
+				
+				None => blank += 1,
+			}
+		}
+		
+		return dict! {
+			"count": sprite_array.len() as i64,
+			"blank": blank,
+			"max_width": max_width,
+			"max_height": max_height,
+			"total_pixels": total_pixels,
+			"depths": depths,
+		};
+	}
+	
+	
+	/// Returns the index of the object's palette that best covers the given sprite,
+	/// i.e. maps the most of its pixels to non-transparent colors. Ties resolve to the
+	/// lowest index. Returns -1 if the object has no palettes.
+	#[func] fn best_palette_for_sprite(dictionary: Dictionary, sprite: Gd<BinSprite>) -> i64 {
+		let palette_array: Array<Gd<BinPalette>>;
+		
+		match dictionary.get("palettes") {
+			Some(value) => palette_array = value.to(),
+			None => return -1,
+		}
+		
+		let histogram: PackedInt64Array = sprite.bind().index_histogram();
+		let mut best_index: i64 = -1;
+		let mut best_score: i64 = -1;
+		
+		for palette_number in 0..palette_array.len() {
+			let palette: Gd<BinPalette> = palette_array.at(palette_number);
+			let colors: PackedByteArray = palette.bind().palette.clone();
+			let mut score: i64 = 0;
+			
+			for index in 0..histogram.len() {
+				let alpha: usize = 4 * index + 3;
+				
+				if alpha < colors.len() && colors[alpha] != 0x00 {
+					score += histogram[index];
+				}
+			}
+			
+			if score > best_score {
+				best_score = score;
+				best_index = palette_number as i64;
+			}
+		}
+		
+		return best_index;
+	}
+	
+	
+	/// Returns the color depth of each sprite in the object.
+	#[func] fn sprite_depths(dictionary: Dictionary) -> PackedInt64Array {
+		let mut depths: Vec<i64> = Vec::new();
+		
+		match dictionary.get("sprites") {
+			Some(value) => {
+				let sprite_array: Array<Gd<BinSprite>> = value.to();
+				
+				for sprite in sprite_array.iter_shared() {
+					depths.push(sprite.bind().bit_depth as i64);
 				}
+			},
+			
+			None => (),
+		}
+		
+		return PackedInt64Array::from(depths);
+	}
+	
+	
+	/// Converts every sprite in the object to the target color depth (4 or 8).
+	/// Returns the indices of sprites that use colors above 15 and would be clamped
+	/// when demoting to 4bpp. Unless `force` is set, nothing is changed if any exist.
+	#[func] fn normalize_depth(dictionary: Dictionary, target: i64, force: bool) -> PackedInt64Array {
+		let mut lossy: Vec<i64> = Vec::new();
+		
+		if target != 4 && target != 8 {
+			log_error!("bin_resource::normalize_depth(): Invalid target depth {}!", target);
+			return PackedInt64Array::from(lossy);
+		}
+		
+		let sprite_array: Array<Gd<BinSprite>>;
+		
+		match dictionary.get("sprites") {
+			Some(value) => sprite_array = value.to(),
+			None => return PackedInt64Array::from(lossy),
+		}
+		
+		for sprite_number in 0..sprite_array.len() {
+			if !sprite_array.at(sprite_number).bind().fits_bit_depth(target as u16) {
+				lossy.push(sprite_number as i64);
 			}
 		}
 		
-		return sprites;
+		if !lossy.is_empty() && !force {
+			return PackedInt64Array::from(lossy);
+		}
+		
+		for mut sprite in sprite_array.iter_shared() {
+			sprite.bind_mut().set_bit_depth(target as u16);
+		}
+		
+		return PackedInt64Array::from(lossy);
 	}
-
-
+	
+	
 	// =================================================================================
 	// SAVING
 	// =================================================================================
 	
 	
+	/// Saves a loaded resource to a BIN file.
+	/// With `verify_after_save`, the written file is identified again and every object
+	/// whose type no longer matches is returned as {index, expected, found}.
 	#[func] pub fn save_resource_file(
-		dictionary: Dictionary, path: String, mut global_signals: Gd<Node>
-	) {
+		dictionary: Dictionary, path: String, global_signals: Gd<Node>, verify_after_save: bool
+	) -> Array<Dictionary> {
+		if dictionary.contains_key("cancelled") {
+			log_error!("Cannot save a partially loaded (cancelled) resource!");
+			return Array::new();
+		}
+		
 		{
 			let mut path_check: PathBuf = PathBuf::from(&path);
 			let _ = path_check.pop();
 			
 			if !path_check.exists() {
-				godot_print!("Path does not exist!");
-				return;
+				log_error!("Path does not exist!");
+				return Array::new();
 			}
 		}
 
-		let reference: &mut Gd<Node> = &mut global_signals;
 		let path_buf: PathBuf = PathBuf::from(path);
-
+		let file_vector: Vec<u8> = Self::serialize_resource(&dictionary, &mut Some(global_signals));
+		
+		//godot_print!("Writing to {:?}", path_buf);
+		
+		match fs::File::create(&path_buf) {
+			Ok(file) => {
+				let ref mut buffer = BufWriter::new(file);
+				let _ = buffer.write_all(&file_vector);
+				let _ = buffer.flush();
+			},
+			
+			_ => (),
+		}
+		
+		if !verify_after_save {
+			return Array::new();
+		}
+		
+		return Self::verify_saved_file(&dictionary, &path_buf);
+	}
+	
+	
+	// Builds the file contents for a loaded resource. Progress is reported through
+	// `reference` if given.
+	fn serialize_resource(dictionary: &Dictionary, reference: &mut Option<Gd<Node>>) -> Vec<u8> {
 		let mut file_vector: Vec<u8> = Vec::new();
 		let mut data_vector: Vec<u8> = Vec::new();
 		let mut header_pointers: Vec<u32> = Vec::new();
+		let mut headerless: bool = false;
 		
 		for (_object_number, object_dict) in dictionary.iter_shared().typed::<i64, Dictionary>() {
 			header_pointers.push(data_vector.len() as u32);
@@ -712,7 +1521,7 @@ impl BinResource {
 			match &this_type as &str {
 				"sprite_list_file" => {
 					// Report
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_object"),
 						Variant::from("SpriteList"),
 					]);
@@ -727,14 +1536,36 @@ impl BinResource {
 					break;
 				}
 				
+				"palette_file" => {
+					// Report
+					Self::emit_deferred(reference, &[
+						Variant::from("save_object"),
+						Variant::from("Palette"),
+					]);
+
+					data_vector.extend(Self::get_bin_palette(object_dict));
+					headerless = true;
+					break;
+				}
+				
+				"palette" => {
+					// Report
+					Self::emit_deferred(reference, &[
+						Variant::from("save_object"),
+						Variant::from("Palette"),
+					]);
+
+					data_vector.extend(Self::get_bin_palette(object_dict));
+				}
+				
 				"sprite" => {
 					// Report
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_object"),
 						Variant::from("Sprite"),
 					]);
 
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_sub_object"),
 						Variant::from("Single sprite"),
 					]);
@@ -744,7 +1575,7 @@ impl BinResource {
 					
 				"sprite_list" => {
 					// Report
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_object"),
 						Variant::from("SpriteList"),
 					]);
@@ -754,7 +1585,7 @@ impl BinResource {
 					
 				"sprite_list_select" => {
 					// Report
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_object"),
 						Variant::from("SpriteListSelect"),
 					]);
@@ -766,7 +1597,7 @@ impl BinResource {
 					
 				"jpf_plain_text" => {
 					// Report
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_object"),
 						Variant::from("JPFPlainText"),
 					]);
@@ -776,7 +1607,7 @@ impl BinResource {
 					
 				"scriptable" => {
 					// Report
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_object"),
 						Variant::from("Scriptable"),
 					]);
@@ -786,7 +1617,7 @@ impl BinResource {
 					
 				"multi_scriptable" => {
 					// Report
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_object"),
 						Variant::from("MultiScriptable"),
 					]);
@@ -796,12 +1627,12 @@ impl BinResource {
 					
 				_ => {
 					// Report
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_object"),
 						Variant::from("Unsupported"),
 					]);
 
-					reference.call_deferred("emit_signal", &[
+					Self::emit_deferred(reference, &[
 						Variant::from("save_sub_object"),
 						Variant::from("Raw bytes"),
 					]);
@@ -812,19 +1643,117 @@ impl BinResource {
 			}
 		}
 		
-		file_vector.extend(Self::finalize_pointers(header_pointers));
+		if !headerless {
+			file_vector.extend(Self::finalize_pointers(header_pointers));
+		}
+		
 		file_vector.extend(data_vector);
 		
-		//godot_print!("Writing to {:?}", path_buf);
+		return file_vector;
+	}
+	
+	
+	// Emits a save progress signal on the next idle frame, if there is a node to emit it from.
+	fn emit_deferred(global_signals: &mut Option<Gd<Node>>, args: &[Variant]) {
+		match global_signals {
+			Some(node) => {
+				node.call_deferred("emit_signal", args);
+			},
+			
+			None => (),
+		}
+	}
+	
+	
+	// Re-identifies every object in a saved file, returning those that don't match the session.
+	fn verify_saved_file(dictionary: &Dictionary, path_buf: &PathBuf) -> Array<Dictionary> {
+		let mut mismatches: Array<Dictionary> = Array::new();
+		let bin_data: Vec<u8>;
 		
-		match fs::File::create(path_buf) {
-			Ok(file) => {
-				let ref mut buffer = BufWriter::new(file);
-				let _ = buffer.write_all(&file_vector);
-				let _ = buffer.flush();
+		match fs::read(path_buf) {
+			Ok(data) => bin_data = data,
+			_ => {
+				mismatches.push(&dict! {
+					"index": -1,
+					"expected": "file",
+					"found": "unreadable",
+				});
+				return mismatches;
 			},
+		}
+		
+		let objects: Vec<Vec<u8>> = Self::get_objects(&bin_data);
+		
+		for (index, (_key, object_dict)) in dictionary.iter_shared().typed::<i64, Dictionary>().enumerate() {
+			let expected: String = object_dict.get("type").map_or(String::new(), |value| value.to_string());
+			let found: String;
 			
-			_ => (),
+			match &expected as &str {
+				// Passthrough data, anything goes
+				"unsupported" => continue,
+				
+				// Whole-file types, only the first object is saved
+				"palette_file" => {
+					if identify_palette(&bin_data) {
+						break;
+					}
+					
+					found = String::from("unsupported");
+				},
+				
+				"sprite_list_file" => {
+					let sprite_list: bool = !objects.is_empty() && objects.iter().all(
+						|object| Self::object_type_name(&identify_object(object)) == "sprite"
+					);
+					
+					if sprite_list {
+						break;
+					}
+					
+					found = String::from("unsupported");
+				},
+				
+				_ => {
+					if index >= objects.len() {
+						found = String::from("missing");
+					} else {
+						found = String::from(Self::object_type_name(&identify_object(&objects[index])));
+					}
+				},
+			}
+			
+			if found != expected {
+				mismatches.push(&dict! {
+					"index": index as i64,
+					"expected": expected.clone(),
+					"found": found,
+				});
+			}
+			
+			if expected == "palette_file" || expected == "sprite_list_file" {
+				break;
+			}
+		}
+		
+		if !mismatches.is_empty() {
+			log_error!("Saved file does not re-identify correctly: {} mismatched object(s)", mismatches.len());
+		}
+		
+		return mismatches;
+	}
+	
+	
+	// Maps identified object types to the "type" strings used in loaded dictionaries.
+	fn object_type_name(object_type: &ObjectType) -> &'static str {
+		match object_type {
+			ObjectType::Sprite => return "sprite",
+			ObjectType::SpriteList => return "sprite_list",
+			ObjectType::SpriteListSelect => return "sprite_list_select",
+			ObjectType::JPFPlainText => return "jpf_plain_text",
+			ObjectType::Palette => return "palette",
+			ObjectType::Scriptable => return "scriptable",
+			ObjectType::MultiScriptable => return "multi_scriptable",
+			_ => return "unsupported",
 		}
 	}
 	
@@ -836,7 +1765,7 @@ impl BinResource {
 			match fs::create_dir_all(&path) {
 				Ok(_result) => (),
 				_ => {
-					godot_print!("Path does not exist and could not be created!");
+					log_error!("Path does not exist and could not be created!");
 					return;
 				}
 			}
@@ -844,11 +1773,13 @@ impl BinResource {
 
 		let reference: &mut Gd<Node> = &mut global_signals;
 		let dictionary: Dictionary = session.at("data").to();
+		let mut object_order: Vec<String> = Vec::new();
 		
 		for object in 0..dictionary.len() {
 			let object_dict: Dictionary = dictionary.at(object as i64).to();
 			let mut object_path: String = path.clone();
 			let object_name: String = object_dict.at("name").to();
+			object_order.push(object_name.clone());
 			let push: String = format!("/{}", object_name);
 			object_path.push_str(&push);
 			
@@ -901,6 +1832,34 @@ impl BinResource {
 				Self::save_palettes_to_path(palette_array, &object_path, reference);
 			}
 		}
+		
+		Self::save_object_order(object_order, &path);
+	}
+	
+	
+	fn save_object_order(object_order: Vec<String>, path: &String) {
+		match serde_json::to_string_pretty(&object_order) {
+			Ok(string) => match fs::write(format!("{}/order.json", path), string) {
+				Ok(_result) => (),
+				_ => log_error!("bin_resource::save_object_order(): Could not write order.json!"),
+			},
+			
+			_ => (),
+		}
+	}
+	
+	
+	/// Returns the smallest file the loader accepts: a sprite list holding a single
+	/// uncompressed, palette-less 1x1 sprite. 48 bytes long.
+	#[func] pub fn make_minimal_sprite_list() -> PackedByteArray {
+		return PackedByteArray::from(Self::minimal_sprite_list());
+	}
+	
+	
+	fn minimal_sprite_list() -> Vec<u8> {
+		let mut file_vector: Vec<u8> = Self::finalize_pointers(vec![0x00]);
+		file_vector.extend(bin_sprite::make_blank_sprite());
+		return file_vector;
 	}
 	
 	
@@ -935,7 +1894,7 @@ impl BinResource {
 		match fs::create_dir_all(&sprites_0_path) {
 			Ok(_result) => (),
 			_ => {
-				godot_print!(
+				log_error!(
 					"bin_resource::save_sprites_to_path(): Path does not exist and could not be created!"
 				);
 				return;
@@ -962,14 +1921,14 @@ impl BinResource {
 					let sprite = gd_sprite.bind();
 					match file.write(sprite.to_bin().as_slice()) {
 						Ok(_t) => (),
-						_ => godot_print!(
+						_ => log_error!(
 							"bin_resource::save_sprites_to_path(): Failed to write sprite_{}.bin!",
 							sprite_number
 						),
 					}
 				},
 				_ => {
-					godot_print!(
+					log_error!(
 						"bin_resource::save_sprites_to_path(): Could not create sprite_{}.bin!",
 						sprite_number
 					);
@@ -1049,23 +2008,45 @@ impl BinResource {
 	
 	
 	// Returns (non-finalized) pointer vector and sprite vector
+	// The loader requires at least one sprite, so an empty array is written as
+	// a single blank 1x1 sprite (see bin_sprite::make_blank_sprite()).
 	fn get_sprite_block(
-		sprite_array: Array<Gd<BinSprite>>, offset: u32, global_signals: &mut Gd<Node>
+		sprite_array: Array<Gd<BinSprite>>, offset: u32, global_signals: &mut Option<Gd<Node>>
 	) -> (Vec<u32>, Vec<u8>) {
-		let mut pointer_vector: Vec<u32> = Vec::new();
-		let mut sprite_vector: Vec<u8> = Vec::new();
+		let mut sprite_bins: Vec<Vec<u8>> = Vec::new();
 		
 		for item in 0..sprite_array.len() {
-			global_signals.call_deferred(
-				"emit_signal", &[
+			Self::emit_deferred(
+				global_signals, &[
 					Variant::from("save_sub_object"),
 					Variant::from(format!("Sprite # {}", item)),
 				]
 			);
 
-			pointer_vector.push(sprite_vector.len() as u32 + offset);
 			let sprite: Gd<BinSprite> = sprite_array.at(item);
-			sprite_vector.extend(sprite.bind().to_bin());
+			sprite_bins.push(sprite.bind().to_bin());
+		}
+		
+		return Self::build_sprite_block(sprite_bins, offset);
+	}
+	
+	
+	// Lays out already encoded sprites, with pointers starting at `offset`.
+	// An empty block gets a blank placeholder sprite, as the game expects at least one.
+	fn build_sprite_block(sprite_bins: Vec<Vec<u8>>, offset: u32) -> (Vec<u32>, Vec<u8>) {
+		let mut pointer_vector: Vec<u32> = Vec::new();
+		let mut sprite_vector: Vec<u8> = Vec::new();
+		
+		if sprite_bins.is_empty() {
+			log_warn!("Object has no sprites, writing a blank placeholder sprite");
+			pointer_vector.push(offset);
+			sprite_vector.extend(bin_sprite::make_blank_sprite());
+			return (pointer_vector, sprite_vector);
+		}
+		
+		for sprite_bin in sprite_bins {
+			pointer_vector.push(sprite_vector.len() as u32 + offset);
+			sprite_vector.extend(sprite_bin);
 		}
 		
 		return (pointer_vector, sprite_vector);
@@ -1073,14 +2054,14 @@ impl BinResource {
 	
 	
 	fn get_cell_block(
-		cell_array: Array<Gd<Cell>>, global_signals: &mut Gd<Node>
+		cell_array: Array<Gd<Cell>>, global_signals: &mut Option<Gd<Node>>
 	) -> (Vec<u32>, Vec<u8>) {
 		let mut pointer_vector: Vec<u32> = Vec::new();
 		let mut cell_vector: Vec<u8> = Vec::new();
 		
 		for item in 0..cell_array.len() {
-			global_signals.call_deferred(
-				"emit_signal", &[
+			Self::emit_deferred(
+				global_signals, &[
 					Variant::from("save_sub_object"),
 					Variant::from(format!("Cell # {}", item)),
 				]
@@ -1096,14 +2077,14 @@ impl BinResource {
 	
 	
 	fn get_palette_block(
-		palette_array: Array<Gd<BinPalette>>, global_signals: &mut Gd<Node>
+		palette_array: Array<Gd<BinPalette>>, global_signals: &mut Option<Gd<Node>>
 	) -> (Vec<u32>, Vec<u8>) {
 		let mut pointer_vector: Vec<u32> = Vec::new();
 		let mut palette_vector: Vec<u8> = Vec::new();
 		
 		for item in 0..palette_array.len() {
-			global_signals.call_deferred(
-				"emit_signal", &[
+			Self::emit_deferred(
+				global_signals, &[
 					Variant::from("save_sub_object"),
 					Variant::from(format!("Palette # {}", item)),
 				]
@@ -1123,12 +2104,31 @@ impl BinResource {
 	
 	fn get_bin_sprite(dictionary: Dictionary) -> Vec<u8> {
 		let sprite_array: Array<Gd<BinSprite>> = dictionary.at("sprites").to();
+		
+		if sprite_array.is_empty() {
+			log_warn!("Sprite object is empty, writing a blank placeholder sprite");
+			return bin_sprite::make_blank_sprite();
+		}
+		
 		let sprite: Gd<BinSprite> = sprite_array.at(0);
 		return sprite.bind().to_bin();
 	}
 	
 	
-	fn get_bin_sprite_list(dictionary: Dictionary, global_signals: &mut Gd<Node>) -> Vec<u8> {
+	fn get_bin_palette(dictionary: Dictionary) -> Vec<u8> {
+		let palette_array: Array<Gd<BinPalette>> = dictionary.at("palettes").to();
+		
+		if palette_array.is_empty() {
+			log_warn!("Palette object is empty, writing a transparent 16 color palette");
+			return BinPalette::palette_to_bin(vec![0x00; 16 * 4]);
+		}
+		
+		let palette: Gd<BinPalette> = palette_array.at(0);
+		return palette.bind().to_bin();
+	}
+	
+	
+	fn get_bin_sprite_list(dictionary: Dictionary, global_signals: &mut Option<Gd<Node>>) -> Vec<u8> {
 		let sprite_array: Array<Gd<BinSprite>> = dictionary.at("sprites").to();
 		
 		let header_pointers: Vec<u32>;
@@ -1145,7 +2145,7 @@ impl BinResource {
 	}
 	
 	
-	fn get_bin_sprite_list_select(dictionary: Dictionary, global_signals: &mut Gd<Node>) -> Vec<u8> {
+	fn get_bin_sprite_list_select(dictionary: Dictionary, global_signals: &mut Option<Gd<Node>>) -> Vec<u8> {
 		/* Dictionary contents:
 		 * "type": "sprite_list_select"
 		 * "sprites": Array<Gd<BinSprite>>
@@ -1192,7 +2192,7 @@ impl BinResource {
 	}
 
 
-	fn get_bin_jpf_plain_text(dictionary: Dictionary, global_signals: &mut Gd<Node>) -> Vec<u8> {
+	fn get_bin_jpf_plain_text(dictionary: Dictionary, global_signals: &mut Option<Gd<Node>>) -> Vec<u8> {
 		/* Dictionary contents:
 		 * "type": "jpf_plain_text",
 		 * "char_index": PackedByteArray,
@@ -1232,7 +2232,7 @@ impl BinResource {
 	}
 	
 	
-	fn get_bin_scriptable(dictionary: Dictionary, global_signals: &mut Gd<Node>) -> Vec<u8> {
+	fn get_bin_scriptable(dictionary: Dictionary, global_signals: &mut Option<Gd<Node>>) -> Vec<u8> {
 		/* Dictionary contents:
 		 * "type": "scriptable",
 		 * "name": String,
@@ -1250,50 +2250,56 @@ impl BinResource {
 		 * palettes
 		 */
 		
-		let mut header_pointers: Vec<u32> = Vec::new();
-		let mut data_vector: Vec<u8> = Vec::new();
-		
 		// Cells
 		let cell_array: Array<Gd<Cell>> = dictionary.at("cells").to();
-		let cell_tuple: (Vec<u32>, Vec<u8>) = Self::get_cell_block(cell_array, global_signals);
-		let cell_pointers: Vec<u8> = Self::finalize_pointers(cell_tuple.0);
+		let cell_block: (Vec<u32>, Vec<u8>) = Self::get_cell_block(cell_array, global_signals);
 		
 		// Sprites
 		let sprite_array: Array<Gd<BinSprite>> = dictionary.at("sprites").to();
-		let sprite_tuple: (Vec<u32>, Vec<u8>) = Self::get_sprite_block(
+		let sprite_block: (Vec<u32>, Vec<u8>) = Self::get_sprite_block(
 			sprite_array, 0x00, global_signals);
-		let sprite_pointers: Vec<u8> = Self::finalize_pointers(sprite_tuple.0);
 		
 		// Scripts
 		let scripts_array: PackedByteArray = dictionary.at("scripts").to();
 		let scripts: Vec<u8> = scripts_array.to_vec();
 		
-		// Start writing...
+		// Palettes
+		let palette_block: (Vec<u32>, Vec<u8>);
+		
+		match dictionary.get("palettes") {
+			Some(value) => palette_block = Self::get_palette_block(value.to(), global_signals),
+			None => palette_block = (Vec::new(), Vec::new()),
+		}
+		
+		return Self::build_scriptable(cell_block, sprite_block, scripts, palette_block);
+	}
+	
+	
+	// Lays out a scriptable from its encoded blocks.
+	fn build_scriptable(
+		cell_block: (Vec<u32>, Vec<u8>), sprite_block: (Vec<u32>, Vec<u8>),
+		scripts: Vec<u8>, palette_block: (Vec<u32>, Vec<u8>)
+	) -> Vec<u8> {
+		let mut header_pointers: Vec<u32> = Vec::new();
+		let mut data_vector: Vec<u8> = Vec::new();
+		
 		header_pointers.push(data_vector.len() as u32);
-		data_vector.extend(cell_pointers);
-		data_vector.extend(cell_tuple.1);
+		data_vector.extend(Self::finalize_pointers(cell_block.0));
+		data_vector.extend(cell_block.1);
 		
 		header_pointers.push(data_vector.len() as u32);
-		data_vector.extend(sprite_pointers);
-		data_vector.extend(sprite_tuple.1);
+		data_vector.extend(Self::finalize_pointers(sprite_block.0));
+		data_vector.extend(sprite_block.1);
 		
 		header_pointers.push(data_vector.len() as u32);
 		data_vector.extend(scripts);
 		
-		match dictionary.get("palettes") {
-			Some(value) => {
-				let palette_array: Array<Gd<BinPalette>> = value.to();
-				let palette_tuple: (Vec<u32>, Vec<u8>) = Self::get_palette_block(
-					palette_array, global_signals
-				);
-				let palette_pointers: Vec<u8> = Self::finalize_pointers(palette_tuple.0);
-				
-				header_pointers.push(data_vector.len() as u32);
-				data_vector.extend(palette_pointers);
-				data_vector.extend(palette_tuple.1);
-			}
-			
-			_ => (),
+		// Non-player objects have exactly three sections (cells, sprites, scripts);
+		// an empty palette block must not add a fourth
+		if !palette_block.0.is_empty() {
+			header_pointers.push(data_vector.len() as u32);
+			data_vector.extend(Self::finalize_pointers(palette_block.0));
+			data_vector.extend(palette_block.1);
 		}
 		
 		let mut object_vector: Vec<u8> = Self::finalize_pointers(header_pointers);
@@ -1303,7 +2309,7 @@ impl BinResource {
 	}
 	
 	
-	fn get_bin_multi_scriptable(dictionary: Dictionary, global_signals: &mut Gd<Node>) -> Vec<u8> {
+	fn get_bin_multi_scriptable(dictionary: Dictionary, global_signals: &mut Option<Gd<Node>>) -> Vec<u8> {
 		/* Dictionary contents:
 		 * "type": "multi_scriptable",
 		 * "data": Dictionary {
@@ -1335,4 +2341,210 @@ impl BinResource {
 		
 		return object_vector;
 	}
+}
+
+
+#[derive(GodotClass)]
+#[class(tool, base=Resource)]
+/// Lazily loaded BIN resource file. Only the header pointers are read up front,
+/// each object is parsed when requested with load_object().
+struct BinArchive {
+	base: Base<Resource>,
+	bin_data: Vec<u8>,
+	object_ranges: Vec<(usize, usize)>,
+}
+
+
+#[godot_api]
+impl IResource for BinArchive {
+	fn init(base: Base<Resource>) -> Self {
+		Self {
+			base,
+			bin_data: Vec::new(),
+			object_ranges: Vec::new(),
+		}
+	}
+}
+
+
+#[godot_api]
+impl BinArchive {
+	/// Opens a BIN resource file for lazy access. Returns null if it can't be read or has no objects.
+	#[func] fn from_file(source_path: String) -> Option<Gd<BinArchive>> {
+		let bin_data: Vec<u8>;
+		
+		match fs::read(&source_path) {
+			Ok(data) => bin_data = data,
+			_ => {
+				log_error!("BinArchive::from_file() error: Could not read {}", source_path);
+				return None;
+			},
+		}
+		
+		let object_ranges: Vec<(usize, usize)> = BinResource::get_object_ranges(&bin_data);
+		
+		if object_ranges.len() == 0 {
+			log_error!("BinArchive::from_file() error: No objects in {}", source_path);
+			return None;
+		}
+		
+		return Some(Gd::from_init_fn(|base| {
+			Self {
+				base,
+				bin_data,
+				object_ranges,
+			}
+		}));
+	}
+	
+	
+	/// Returns the number of objects in the file.
+	#[func] fn object_count(&self) -> i64 {
+		return self.object_ranges.len() as i64;
+	}
+	
+	
+	/// Returns the type an object is identified as, without loading it.
+	/// Uses the same names as BinResource's "type" keys, or "" if out of range.
+	#[func] fn object_type(&self, index: i64) -> GString {
+		match self.object_data(index) {
+			Some(object_bin_data) => return BinResource::object_type_name(&identify_object(object_bin_data)).into(),
+			None => return GString::new(),
+		}
+	}
+	
+	
+	/// Parses and returns a single object, as found in BinResource.from_file()'s result.
+	/// Unnamed scriptables are numbered by object index rather than by scriptable count.
+	#[func] fn load_object(&self, index: i64) -> Dictionary {
+		let object_bin_data: Vec<u8>;
+		
+		match self.object_data(index) {
+			Some(data) => object_bin_data = data.to_vec(),
+			None => {
+				log_error!("BinArchive::load_object() error: Index {} out of range", index);
+				return dict! {
+					"error": "Index out of range",
+				};
+			},
+		}
+		
+		let mut object_number: usize = index as usize;
+		return BinResource::load_object(&object_bin_data, &mut object_number);
+	}
+	
+	
+	fn object_data(&self, index: i64) -> Option<&[u8]> {
+		if index < 0 || index as usize >= self.object_ranges.len() {
+			return None;
+		}
+		
+		let (start, end): (usize, usize) = self.object_ranges[index as usize];
+		return Some(&self.bin_data[start..end]);
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sprite_get;
+	use crate::sprite_compress::SpriteData;
+	
+	
+	#[test]
+	fn minimal_sprite_list_is_accepted() {
+		let bin_data: Vec<u8> = BinResource::minimal_sprite_list();
+		
+		assert_eq!(bin_data.len(), 0x30);
+		assert!(matches!(identify_object(&bin_data), ObjectType::SpriteList));
+		assert!(matches!(BinResource::check_binary_data(&bin_data), Ok(FileKind::SpriteListFile)));
+	}
+	
+	
+	#[test]
+	fn object_order_round_trips() {
+		let directory: PathBuf = std::env::temp_dir().join("ggpr_bin_object_order");
+		let _ = fs::create_dir_all(&directory);
+		
+		let saved: Vec<String> = vec![String::from("effect"), String::from("player"), String::from("object_10")];
+		BinResource::save_object_order(saved.clone(), &directory.to_string_lossy().to_string());
+		let loaded: Option<Vec<String>> = BinResource::load_object_order(&directory);
+		assert_eq!(loaded, Some(saved));
+		
+		// Found on disk in a different order, plus one object order.json doesn't list
+		let names: Vec<String> = ["object_10", "object_2", "player", "effect"].iter().map(|name| name.to_string()).collect();
+		assert_eq!(BinResource::order_object_names(&names, &loaded, false), vec![3, 2, 0, 1]);
+		
+		let _ = fs::remove_dir_all(&directory);
+	}
+	
+	
+	#[test]
+	fn objects_without_order_sort_naturally() {
+		let names: Vec<String> = ["object_10", "player", "object_2"].iter().map(|name| name.to_string()).collect();
+		
+		assert_eq!(BinResource::order_object_names(&names, &None, false), vec![2, 0, 1]);
+		assert_eq!(BinResource::order_object_names(&names, &None, true), vec![1, 2, 0]);
+	}
+	
+	
+	#[test]
+	fn empty_sprite_block_has_placeholder() {
+		let (pointers, data): (Vec<u32>, Vec<u8>) = BinResource::build_sprite_block(Vec::new(), 0x20);
+		
+		assert_eq!(pointers, vec![0x20]);
+		assert_eq!(data.len() % 0x10, 0);
+		assert!(matches!(identify_object(&data), ObjectType::Sprite));
+		
+		let placeholder: SpriteData = sprite_get::get_bin_data(&data).unwrap();
+		assert_eq!((placeholder.width, placeholder.height), (1, 1));
+	}
+	
+	
+	#[test]
+	fn content_hash_tracks_every_part() {
+		let sprites: Vec<u64> = vec![0x1234, 0x5678];
+		let cells: Vec<Vec<u8>> = vec![vec![0x00; 0x10]];
+		let scripts: Vec<Vec<u8>> = vec![vec![0x01, 0x02, 0xFF]];
+		let palettes: Vec<Vec<u8>> = vec![vec![0x80; 0x40]];
+		
+		let hash = |sprites: &[u64], cells: &[Vec<u8>], scripts: &[Vec<u8>], palettes: &[Vec<u8>]| -> u64 {
+			return BinResource::hash_object_parts(
+				bin_sprite::FNV_OFFSET, "scriptable", sprites, cells, scripts, palettes
+			);
+		};
+		
+		let original: u64 = hash(&sprites, &cells, &scripts, &palettes);
+		assert_eq!(hash(&sprites, &cells, &scripts, &palettes), original);
+		
+		assert_ne!(hash(&[0x1234, 0x5679], &cells, &scripts, &palettes), original);
+		assert_ne!(hash(&sprites, &[vec![0x01; 0x10]], &scripts, &palettes), original);
+		assert_ne!(hash(&sprites, &cells, &[vec![0x01, 0x03, 0xFF]], &palettes), original);
+		assert_ne!(hash(&sprites, &cells, &scripts, &[vec![0x7F; 0x40]]), original);
+	}
+	
+	
+	#[test]
+	fn scriptable_without_palettes_keeps_three_sections() {
+		let cell: Vec<u8> = vec![0x00; 0x10];
+		let sprite: Vec<u8> = bin_sprite::make_blank_sprite();
+		let scripts: Vec<u8> = vec![0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0xFF];
+		
+		let bin_data: Vec<u8> = BinResource::build_scriptable(
+			(vec![0x00], cell.clone()),
+			BinResource::build_sprite_block(vec![sprite.clone()], 0x00),
+			scripts.clone(),
+			(Vec::new(), Vec::new()),
+		);
+		
+		let pointers: Vec<usize> = get_pointers(&bin_data, 0x00, false);
+		assert_eq!(pointers.len(), 3);
+		assert!(matches!(identify_object(&bin_data), ObjectType::Scriptable));
+		
+		// Each section holds a one-entry pointer table, then the data
+		assert_eq!(&bin_data[pointers[0] + 0x10..pointers[1]], cell.as_slice());
+		assert_eq!(&bin_data[pointers[1] + 0x10..pointers[2]], sprite.as_slice());
+		assert_eq!(BinResource::load_scripts(&bin_data, &pointers), scripts);
+	}
 }
\ No newline at end of file
@@ -1,5 +1,49 @@
+use serde::Serialize;
+use serde::Deserialize;
+
 use godot::prelude::*;
 
+use crate::logging::*;
+
+const ID_END_ACTION: u8 = 0xFF;
+
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ScriptJSONArgument {
+	name: String,
+	size: u8,
+	signed: bool,
+	value: i64,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ScriptJSONInstruction {
+	id: u8,
+	name: String,
+	#[serde(default)]
+	arguments: Vec<ScriptJSONArgument>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ScriptJSONAction {
+	flags: u32,
+	lvflag: u16,
+	damage: u8,
+	flag2: u8,
+	instructions: Vec<ScriptJSONInstruction>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ScriptJSON {
+	// play_data as space-separated hex bytes, kept as-is for byte-exact round trips
+	#[serde(default)]
+	variables: String,
+	actions: Vec<ScriptJSONAction>,
+}
+
 
 // Class definitions
 
@@ -9,7 +53,7 @@ use godot::prelude::*;
 pub struct InstructionArgument {
 	base: Base<Resource>,
 	#[export] pub display_name: GString,
-	/// Size of this argument. Possible values: 1, 2.
+	/// Size of this argument. Possible values: 1, 2, 4.
 	#[export] pub size: u8,
 	/// Value of this argument. 8, 16, or 32 bits.
 	#[export] pub value: i64,
@@ -119,17 +163,56 @@ pub struct BinScript {
 #[godot_api] impl InstructionArgument {
 	/// Returns a binary representation of this argument.
 	pub fn to_bin(&self) -> Vec<u8> {
-		let mut bin_data: Vec<u8> = Vec::new();
-		
-		if self.size == 1 {
-			bin_data.push(self.value as u8);
-		}
+		return encode_argument(self.value, self.size);
+	}
+	
+	
+	/// Returns the value as the game reads it, sign-extended or zero-extended by size.
+	/// Use this for text and JSON output so negative values aren't shown as large positives.
+	#[func] pub fn normalized_value(&self) -> i64 {
+		return normalize_argument(self.value, self.size, self.signed);
+	}
+	
+	
+	// Returns this argument as text, e.g. `s16 "X Offset" = -12`.
+	pub fn to_text(&self) -> String {
+		return format!("{} {} = {}", self.type_name(), quote_text(&self.display_name.to_string()), self.normalized_value());
+	}
+	
+	
+	// Returns the argument's type as written in script text: u8, s8, u16, s16, u32, or s32.
+	pub fn type_name(&self) -> String {
+		let bits: u8 = match self.size {
+			1 => 8,
+			2 => 16,
+			_ => 32,
+		};
 		
-		else {
-			bin_data.extend(self.value.to_le_bytes());
+		if self.signed {
+			return format!("s{}", bits);
+		} else {
+			return format!("u{}", bits);
 		}
-		
-		return bin_data;
+	}
+	
+	
+	// Sets the value from little-endian bytes, honoring size and signedness.
+	pub fn set_from_bin(&mut self, bin_data: &[u8]) {
+		self.value = decode_argument(bin_data, self.size, self.signed);
+	}
+	
+	
+	// Returns a fresh copy of this argument.
+	pub fn deep_copy(&self) -> Gd<Self> {
+		return Gd::from_init_fn(|base| {
+			Self {
+				base,
+				display_name: self.display_name.clone(),
+				size: self.size,
+				value: self.value,
+				signed: self.signed,
+			}
+		});
 	}
 }
 
@@ -137,16 +220,52 @@ pub struct BinScript {
 #[godot_api] impl Instruction {
 	/// Returns a binary representation of this instruction.
 	pub fn to_bin(&self) -> Vec<u8> {
-		let mut bin_data: Vec<u8> = Vec::new();
-		
-		bin_data.push(self.id);
+		let mut arguments: Vec<Vec<u8>> = Vec::new();
 		
 		for argument in self.arguments.iter_shared() {
 			let item = argument.bind();
-			bin_data.extend(item.to_bin());
+			arguments.push(item.to_bin());
 		}
 		
-		return bin_data;
+		return encode_instruction(self.id, &arguments);
+	}
+	
+	
+	// Returns this instruction as a single line of text, e.g. `0x01 "Cell" (u16 "Cell" = 3)`.
+	pub fn to_text(&self) -> String {
+		let mut text: String = format!("0x{:02X} {}", self.id, quote_text(&self.display_name.to_string()));
+		
+		if self.arguments.len() == 0 {
+			return text;
+		}
+		
+		let mut arguments: Vec<String> = Vec::new();
+		
+		for argument in self.arguments.iter_shared() {
+			arguments.push(argument.bind().to_text());
+		}
+		
+		text.push_str(&format!(" ({})", arguments.join(", ")));
+		return text;
+	}
+	
+	
+	// Returns a fresh copy of this instruction and all of its arguments.
+	pub fn deep_copy(&self) -> Gd<Self> {
+		let mut arguments: Array<Gd<InstructionArgument>> = Array::new();
+		
+		for argument in self.arguments.iter_shared() {
+			arguments.push(&argument.bind().deep_copy());
+		}
+		
+		return Gd::from_init_fn(|base| {
+			Self {
+				base,
+				id: self.id,
+				display_name: self.display_name.clone(),
+				arguments: arguments,
+			}
+		});
 	}
 
 
@@ -166,19 +285,98 @@ pub struct BinScript {
 #[godot_api] impl ScriptAction {
 	/// Returns a binary representation of this action.
 	pub fn to_bin(&self) -> Vec<u8> {
-		let mut bin_data: Vec<u8> = Vec::new();
+		let mut instructions: Vec<Vec<u8>> = Vec::new();
 		
-		bin_data.extend(self.flags.to_le_bytes());
-		bin_data.extend(self.lvflag.to_le_bytes());
-		bin_data.push(self.damage);
-		bin_data.push(self.flag2);
+		for instruction in self.instructions.iter_shared() {
+			let item = instruction.bind();
+			instructions.push(item.to_bin());
+		}
+		
+		return encode_action(self.flags, self.lvflag, self.damage, self.flag2, &instructions);
+	}
+	
+	
+	/// Returns how many times the instruction `id` appears in this action.
+	#[func] pub fn count_instruction(&self, id: u8) -> i64 {
+		let mut count: i64 = 0;
+		
+		for instruction in self.instructions.iter_shared() {
+			if instruction.bind().id == id {
+				count += 1;
+			}
+		}
+		
+		return count;
+	}
+	
+	
+	// Returns this action as text: a header line, then one indented line per instruction.
+	// The end-of-action instruction is set apart by a blank line.
+	pub fn to_text(&self, index: usize) -> String {
+		let mut text: String = format!(
+			"action {}: flags=0x{:08X} lvflag=0x{:04X} damage={} flag2=0x{:02X}\n",
+			index, self.flags, self.lvflag, self.damage, self.flag2
+		);
 		
 		for instruction in self.instructions.iter_shared() {
 			let item = instruction.bind();
-			bin_data.extend(item.to_bin());
+			
+			if item.id == ID_END_ACTION {
+				text.push_str("\n");
+			}
+			
+			text.push_str(&format!("\t{}\n", item.to_text()));
 		}
 		
-		return bin_data;
+		return text;
+	}
+	
+	
+	/// Returns a new action with default header fields and a lone end-of-action
+	/// instruction (ID 0xFF), so it serializes to a valid, terminated block.
+	#[func] pub fn new_empty() -> Gd<Self> {
+		let mut instructions: Array<Gd<Instruction>> = Array::new();
+		
+		instructions.push(&Gd::from_init_fn(|base| {
+			Instruction {
+				base,
+				id: ID_END_ACTION,
+				display_name: GString::from("EndAction"),
+				arguments: array![],
+			}
+		}));
+		
+		return Gd::from_init_fn(|base| {
+			Self {
+				base,
+				flags: 0,
+				lvflag: 0,
+				damage: 0,
+				flag2: 0,
+				instructions: instructions,
+			}
+		});
+	}
+	
+	
+	// Returns a fresh copy of this action and all of its instructions.
+	pub fn deep_copy(&self) -> Gd<Self> {
+		let mut instructions: Array<Gd<Instruction>> = Array::new();
+		
+		for instruction in self.instructions.iter_shared() {
+			instructions.push(&instruction.bind().deep_copy());
+		}
+		
+		return Gd::from_init_fn(|base| {
+			Self {
+				base,
+				flags: self.flags,
+				lvflag: self.lvflag,
+				damage: self.damage,
+				flag2: self.flag2,
+				instructions: instructions,
+			}
+		});
 	}
 }
 
@@ -196,4 +394,967 @@ pub struct BinScript {
 
 		return bin_data;
 	}
+	
+	
+	/// Returns the script as human-readable text, one line per instruction, grouped by action.
+	/// Instruction and argument names are taken from the script itself, and each argument is
+	/// written with its size and signedness so the text fully describes the binary.
+	#[func] pub fn to_text(&self) -> GString {
+		let mut text: String = String::new();
+		
+		if self.variables.len() > 0 {
+			text.push_str(&format!("variables: {}\n\n", hex_bytes(self.variables.as_slice())));
+		}
+		
+		for action in 0..self.actions.len() {
+			if action > 0 {
+				text.push_str("\n");
+			}
+			
+			text.push_str(&self.actions.at(action).bind().to_text(action));
+		}
+		
+		return text.into();
+	}
+	
+	
+	/// Parses text in the format written by to_text() back into a script.
+	/// Argument values are checked against their declared size and signedness.
+	/// On a parse error, logs the line number and offending token and returns null.
+	#[func] pub fn from_text(source: String) -> Option<Gd<BinScript>> {
+		let variables: Vec<u8>;
+		let actions: Array<Gd<ScriptAction>>;
+		
+		match parse_script_text(&source) {
+			Ok((parsed_variables, parsed_actions)) => {
+				variables = parsed_variables;
+				actions = parsed_actions;
+			},
+			
+			Err(message) => {
+				log_error!("BinScript::from_text() error: {}", message);
+				return None;
+			},
+		}
+		
+		return Some(Gd::from_init_fn(|base| {
+			Self {
+				base,
+				variables: PackedByteArray::from(variables),
+				actions: actions,
+			}
+		}));
+	}
+	
+	
+	/// Serializes the script into a JSON string, for diffing and merging alongside cell JSON.
+	#[func] pub fn to_json(&self) -> GString {
+		let mut actions: Vec<ScriptJSONAction> = Vec::new();
+		
+		for action in self.actions.iter_shared() {
+			let action_binding = action.bind();
+			let mut instructions: Vec<ScriptJSONInstruction> = Vec::new();
+			
+			for instruction in action_binding.instructions.iter_shared() {
+				let instruction_binding = instruction.bind();
+				let mut arguments: Vec<ScriptJSONArgument> = Vec::new();
+				
+				for argument in instruction_binding.arguments.iter_shared() {
+					let argument_binding = argument.bind();
+					
+					arguments.push(ScriptJSONArgument {
+						name: argument_binding.display_name.to_string(),
+						size: argument_binding.size,
+						signed: argument_binding.signed,
+						value: argument_binding.normalized_value(),
+					});
+				}
+				
+				instructions.push(ScriptJSONInstruction {
+					id: instruction_binding.id,
+					name: instruction_binding.display_name.to_string(),
+					arguments,
+				});
+			}
+			
+			actions.push(ScriptJSONAction {
+				flags: action_binding.flags,
+				lvflag: action_binding.lvflag,
+				damage: action_binding.damage,
+				flag2: action_binding.flag2,
+				instructions,
+			});
+		}
+		
+		let script_json: ScriptJSON = ScriptJSON {
+			variables: hex_bytes(self.variables.as_slice()),
+			actions,
+		};
+		
+		return serde_json::to_string_pretty(&script_json).unwrap().into();
+	}
+	
+	
+	/// Builds a script from a JSON string written by to_json().
+	/// Returns null if the JSON is malformed or an argument doesn't fit its size.
+	#[func] pub fn from_json(string: String) -> Option<Gd<BinScript>> {
+		let script_json: ScriptJSON;
+		
+		match serde_json::from_str(&string) {
+			Ok(script) => script_json = script,
+			Err(error) => {
+				log_error!("BinScript::from_json() error: {}", error);
+				return None;
+			},
+		}
+		
+		let variables: Vec<u8>;
+		
+		match parse_hex_bytes(&script_json.variables) {
+			Some(bytes) => variables = bytes,
+			None => {
+				log_error!("BinScript::from_json() error: Invalid variables hex string");
+				return None;
+			},
+		}
+		
+		let mut actions: Array<Gd<ScriptAction>> = Array::new();
+		
+		for (action_index, action_json) in script_json.actions.iter().enumerate() {
+			let mut instructions: Array<Gd<Instruction>> = Array::new();
+			
+			for instruction_json in action_json.instructions.iter() {
+				let mut arguments: Array<Gd<InstructionArgument>> = Array::new();
+				
+				for argument_json in instruction_json.arguments.iter() {
+					let type_name: String = format!(
+						"{}{}", if argument_json.signed { "s" } else { "u" }, argument_json.size as u32 * 8
+					);
+					
+					match argument_type(&type_name) {
+						Some((_, _, min, max)) if argument_json.value >= min && argument_json.value <= max => (),
+						_ => {
+							log_error!(
+								"BinScript::from_json() error: Action {} argument \"{}\" value {} doesn't fit {}",
+								action_index, argument_json.name, argument_json.value, type_name
+							);
+							return None;
+						},
+					}
+					
+					arguments.push(&Gd::from_init_fn(|base| {
+						InstructionArgument {
+							base,
+							display_name: argument_json.name.clone().into(),
+							size: argument_json.size,
+							value: argument_json.value,
+							signed: argument_json.signed,
+						}
+					}));
+				}
+				
+				instructions.push(&Gd::from_init_fn(|base| {
+					Instruction {
+						base,
+						id: instruction_json.id,
+						display_name: instruction_json.name.clone().into(),
+						arguments,
+					}
+				}));
+			}
+			
+			actions.push(&Gd::from_init_fn(|base| {
+				ScriptAction {
+					base,
+					flags: action_json.flags,
+					lvflag: action_json.lvflag,
+					damage: action_json.damage,
+					flag2: action_json.flag2,
+					instructions,
+				}
+			}));
+		}
+		
+		return Some(Gd::from_init_fn(|base| {
+			Self {
+				base,
+				variables: PackedByteArray::from(variables),
+				actions,
+			}
+		}));
+	}
+	
+	
+	/// Returns the indices of every action using the instruction `id` at least once.
+	#[func] pub fn find_actions_with_instruction(&self, id: u8) -> PackedInt64Array {
+		let mut indices: Vec<i64> = Vec::new();
+		
+		for action in 0..self.actions.len() {
+			if self.actions.at(action).bind().count_instruction(id) > 0 {
+				indices.push(action as i64);
+			}
+		}
+		
+		return PackedInt64Array::from(indices);
+	}
+	
+	
+	/// Returns a new script containing only a copy of the action at `index`.
+	/// The copy shares no objects with this script, and carries no play_data variables.
+	/// Returns null if `index` is out of range.
+	#[func] pub fn extract_action(&self, index: i64) -> Option<Gd<BinScript>> {
+		if index < 0 || index as usize >= self.actions.len() {
+			log_error!("BinScript::extract_action() error: Index {} out of range", index);
+			return None;
+		}
+		
+		let action: Gd<ScriptAction> = self.actions.at(index as usize);
+		let mut actions: Array<Gd<ScriptAction>> = Array::new();
+		actions.push(&action.bind().deep_copy());
+		
+		return Some(Gd::from_init_fn(|base| {
+			Self {
+				base,
+				variables: PackedByteArray::new(),
+				actions: actions,
+			}
+		}));
+	}
+	
+	
+	/// Compares this script against another, returning one entry per difference.
+	/// Each entry has an "action" index and a "change" of "added", "removed", or "modified".
+	/// Modified actions also list the differing "fields" and "instructions".
+	/// Differing play_data variables are reported as action -1, change "variables".
+	/// An empty result means both scripts are byte-identical.
+	#[func] pub fn diff(&self, other: Gd<BinScript>) -> Array<Dictionary> {
+		let mut differences: Array<Dictionary> = Array::new();
+		let other_script = other.bind();
+		
+		if self.variables != other_script.variables {
+			differences.push(&dict! {
+				"action": -1,
+				"change": "variables",
+			});
+		}
+		
+		let action_count: usize = std::cmp::max(self.actions.len(), other_script.actions.len());
+		
+		for action in 0..action_count {
+			if action >= self.actions.len() {
+				differences.push(&dict! {
+					"action": action as i64,
+					"change": "added",
+				});
+				continue;
+			}
+			
+			if action >= other_script.actions.len() {
+				differences.push(&dict! {
+					"action": action as i64,
+					"change": "removed",
+				});
+				continue;
+			}
+			
+			let this_action: Gd<ScriptAction> = self.actions.at(action);
+			let other_action: Gd<ScriptAction> = other_script.actions.at(action);
+			let this_binding = this_action.bind();
+			let other_binding = other_action.bind();
+			
+			// Cheap check first
+			if this_binding.to_bin() == other_binding.to_bin() {
+				continue;
+			}
+			
+			let mut fields: Vec<GString> = Vec::new();
+			
+			if this_binding.flags != other_binding.flags {
+				fields.push(GString::from("flags"));
+			}
+			
+			if this_binding.lvflag != other_binding.lvflag {
+				fields.push(GString::from("lvflag"));
+			}
+			
+			if this_binding.damage != other_binding.damage {
+				fields.push(GString::from("damage"));
+			}
+			
+			if this_binding.flag2 != other_binding.flag2 {
+				fields.push(GString::from("flag2"));
+			}
+			
+			let mut instructions: Vec<i64> = Vec::new();
+			let instruction_count: usize = std::cmp::max(
+				this_binding.instructions.len(), other_binding.instructions.len()
+			);
+			
+			for instruction in 0..instruction_count {
+				if instruction >= this_binding.instructions.len()
+				|| instruction >= other_binding.instructions.len() {
+					instructions.push(instruction as i64);
+					continue;
+				}
+				
+				let this_instruction: Gd<Instruction> = this_binding.instructions.at(instruction);
+				let other_instruction: Gd<Instruction> = other_binding.instructions.at(instruction);
+				
+				if this_instruction.bind().to_bin() != other_instruction.bind().to_bin() {
+					instructions.push(instruction as i64);
+				}
+			}
+			
+			differences.push(&dict! {
+				"action": action as i64,
+				"change": "modified",
+				"fields": PackedStringArray::from(fields),
+				"instructions": PackedInt64Array::from(instructions),
+			});
+		}
+		
+		return differences;
+	}
+}
+
+
+// Encodes an argument value as `size` little-endian bytes.
+fn encode_argument(value: i64, size: u8) -> Vec<u8> {
+	// Truncating keeps negative values in two's complement
+	match size {
+		1 => return vec![value as u8],
+		2 => return (value as u16).to_le_bytes().to_vec(),
+		_ => return (value as u32).to_le_bytes().to_vec(),
+	}
+}
+
+
+// Returns the value as the game reads it, sign-extended or zero-extended by size.
+fn normalize_argument(value: i64, size: u8, signed: bool) -> i64 {
+	match (size, signed) {
+		(1, true) => return value as i8 as i64,
+		(1, false) => return value as u8 as i64,
+		(2, true) => return value as i16 as i64,
+		(2, false) => return value as u16 as i64,
+		(_, true) => return value as i32 as i64,
+		(_, false) => return value as u32 as i64,
+	}
+}
+
+
+// Reads an argument value from little-endian bytes, honoring size and signedness.
+fn decode_argument(bin_data: &[u8], size: u8, signed: bool) -> i64 {
+	let mut bytes: [u8; 4] = [0; 4];
+	let length: usize = std::cmp::min(size as usize, bin_data.len()).min(4);
+	bytes[..length].copy_from_slice(&bin_data[..length]);
+	
+	return normalize_argument(u32::from_le_bytes(bytes) as i64, size, signed);
+}
+
+
+// Encodes an instruction from its ID and already encoded arguments.
+fn encode_instruction(id: u8, arguments: &[Vec<u8>]) -> Vec<u8> {
+	let mut bin_data: Vec<u8> = vec![id];
+	
+	for argument in arguments {
+		bin_data.extend(argument);
+	}
+	
+	return bin_data;
+}
+
+
+// Encodes an action from its header fields and already encoded instructions.
+fn encode_action(flags: u32, lvflag: u16, damage: u8, flag2: u8, instructions: &[Vec<u8>]) -> Vec<u8> {
+	let mut bin_data: Vec<u8> = Vec::new();
+	
+	bin_data.extend(flags.to_le_bytes());
+	bin_data.extend(lvflag.to_le_bytes());
+	bin_data.push(damage);
+	bin_data.push(flag2);
+	
+	for instruction in instructions {
+		bin_data.extend(instruction);
+	}
+	
+	return bin_data;
+}
+
+
+// Quotes a name for script text, escaping backslashes and double quotes.
+fn quote_text(text: &str) -> String {
+	return format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+
+// Formats bytes as space-separated uppercase hex pairs.
+fn hex_bytes(bytes: &[u8]) -> String {
+	let mut pairs: Vec<String> = Vec::new();
+	
+	for byte in bytes {
+		pairs.push(format!("{:02X}", byte));
+	}
+	
+	return pairs.join(" ");
+}
+
+
+// Parses space-separated hex pairs, as written by hex_bytes().
+fn parse_hex_bytes(text: &str) -> Option<Vec<u8>> {
+	let mut bytes: Vec<u8> = Vec::new();
+	
+	for pair in text.split_whitespace() {
+		match u8::from_str_radix(pair, 16) {
+			Ok(byte) => bytes.push(byte),
+			_ => return None,
+		}
+	}
+	
+	return Some(bytes);
+}
+
+
+// =================================================================================
+// SCRIPT TEXT PARSING
+// =================================================================================
+
+
+enum TextToken {
+	Word(String),
+	Quoted(String),
+	Symbol(char),
+}
+
+
+impl TextToken {
+	fn describe(&self) -> String {
+		match self {
+			TextToken::Word(word) => return word.clone(),
+			TextToken::Quoted(text) => return quote_text(text),
+			TextToken::Symbol(symbol) => return symbol.to_string(),
+		}
+	}
+}
+
+
+// A tokenized line of script text, consumed front to back.
+struct TextLine {
+	number: usize,
+	tokens: Vec<TextToken>,
+	position: usize,
+}
+
+
+impl TextLine {
+	fn new(number: usize, line: &str) -> Result<TextLine, String> {
+		let mut tokens: Vec<TextToken> = Vec::new();
+		let mut chars = line.chars().peekable();
+		
+		while let Some(&character) = chars.peek() {
+			if character.is_whitespace() {
+				chars.next();
+				continue;
+			}
+			
+			// Comment runs to the end of the line
+			if character == '#' {
+				break;
+			}
+			
+			if character == '"' {
+				chars.next();
+				let mut text: String = String::new();
+				let mut closed: bool = false;
+				
+				while let Some(quoted) = chars.next() {
+					match quoted {
+						'"' => {
+							closed = true;
+							break;
+						},
+						
+						'\\' => match chars.next() {
+							Some(escaped) => text.push(escaped),
+							None => break,
+						},
+						
+						_ => text.push(quoted),
+					}
+				}
+				
+				if !closed {
+					return Err(format!("line {}: unterminated name \"{}", number, text));
+				}
+				
+				tokens.push(TextToken::Quoted(text));
+				continue;
+			}
+			
+			if "():,=".contains(character) {
+				chars.next();
+				tokens.push(TextToken::Symbol(character));
+				continue;
+			}
+			
+			let mut word: String = String::new();
+			
+			while let Some(&next) = chars.peek() {
+				if next.is_whitespace() || "():,=\"#".contains(next) {
+					break;
+				}
+				
+				word.push(next);
+				chars.next();
+			}
+			
+			tokens.push(TextToken::Word(word));
+		}
+		
+		return Ok(TextLine {
+			number,
+			tokens,
+			position: 0,
+		});
+	}
+	
+	
+	fn is_empty(&self) -> bool {
+		return self.tokens.is_empty();
+	}
+	
+	
+	fn at_end(&self) -> bool {
+		return self.position >= self.tokens.len();
+	}
+	
+	
+	fn peek(&self) -> Option<&TextToken> {
+		return self.tokens.get(self.position);
+	}
+	
+	
+	// Builds an error pointing at the current token.
+	fn error(&self, expected: &str) -> String {
+		let found: String = match self.peek() {
+			Some(token) => format!("'{}'", token.describe()),
+			None => "end of line".into(),
+		};
+		
+		return format!("line {}: expected {}, found {}", self.number, expected, found);
+	}
+	
+	
+	fn expect_word(&mut self, expected: &str) -> Result<String, String> {
+		match self.peek() {
+			Some(TextToken::Word(word)) => {
+				let word: String = word.clone();
+				self.position += 1;
+				return Ok(word);
+			},
+			
+			_ => return Err(self.error(expected)),
+		}
+	}
+	
+	
+	fn expect_quoted(&mut self, expected: &str) -> Result<String, String> {
+		match self.peek() {
+			Some(TextToken::Quoted(text)) => {
+				let text: String = text.clone();
+				self.position += 1;
+				return Ok(text);
+			},
+			
+			_ => return Err(self.error(expected)),
+		}
+	}
+	
+	
+	fn expect_symbol(&mut self, symbol: char) -> Result<(), String> {
+		if self.accept_symbol(symbol) {
+			return Ok(());
+		}
+		
+		return Err(self.error(&format!("'{}'", symbol)));
+	}
+	
+	
+	fn accept_symbol(&mut self, symbol: char) -> bool {
+		match self.peek() {
+			Some(TextToken::Symbol(found)) if *found == symbol => {
+				self.position += 1;
+				return true;
+			},
+			
+			_ => return false,
+		}
+	}
+	
+	
+	// Reads an integer word and checks it falls within `min..=max`.
+	fn expect_integer(&mut self, expected: &str, min: i64, max: i64) -> Result<i64, String> {
+		let error: String = self.error(expected);
+		let word: String;
+		
+		match self.expect_word(expected) {
+			Ok(value) => word = value,
+			Err(message) => return Err(message),
+		}
+		
+		match parse_integer(&word) {
+			Some(value) if value >= min && value <= max => return Ok(value),
+			Some(_) => return Err(format!("line {}: value '{}' out of range for {}", self.number, word, expected)),
+			None => return Err(error),
+		}
+	}
+	
+	
+	fn expect_end(&self) -> Result<(), String> {
+		if self.at_end() {
+			return Ok(());
+		}
+		
+		return Err(self.error("end of line"));
+	}
+}
+
+
+// Parses decimal or 0x-prefixed hexadecimal integers, optionally negative.
+fn parse_integer(word: &str) -> Option<i64> {
+	let (negative, digits): (bool, &str) = match word.strip_prefix('-') {
+		Some(rest) => (true, rest),
+		None => (false, word),
+	};
+	
+	let parsed = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+		Some(hex) => i64::from_str_radix(hex, 16),
+		None => digits.parse::<i64>(),
+	};
+	
+	match parsed {
+		Ok(value) if negative => return Some(-value),
+		Ok(value) => return Some(value),
+		_ => return None,
+	}
+}
+
+
+// Returns (size, signed, min, max) for an argument type name.
+fn argument_type(type_name: &str) -> Option<(u8, bool, i64, i64)> {
+	match type_name {
+		"u8" => return Some((1, false, 0, u8::MAX as i64)),
+		"s8" => return Some((1, true, i8::MIN as i64, i8::MAX as i64)),
+		"u16" => return Some((2, false, 0, u16::MAX as i64)),
+		"s16" => return Some((2, true, i16::MIN as i64, i16::MAX as i64)),
+		"u32" => return Some((4, false, 0, u32::MAX as i64)),
+		"s32" => return Some((4, true, i32::MIN as i64, i32::MAX as i64)),
+		_ => return None,
+	}
+}
+
+
+fn parse_script_text(source: &str) -> Result<(Vec<u8>, Array<Gd<ScriptAction>>), String> {
+	let mut variables: Vec<u8> = Vec::new();
+	let mut actions: Array<Gd<ScriptAction>> = Array::new();
+	let mut current_action: Option<Gd<ScriptAction>> = None;
+	
+	for (index, line) in source.lines().enumerate() {
+		let mut text_line: TextLine;
+		
+		match TextLine::new(index + 1, line) {
+			Ok(value) => text_line = value,
+			Err(message) => return Err(message),
+		}
+		
+		if text_line.is_empty() {
+			continue;
+		}
+		
+		let keyword: String = match text_line.peek() {
+			Some(TextToken::Word(word)) => word.clone(),
+			_ => String::new(),
+		};
+		
+		match keyword.as_str() {
+			"variables" => {
+				if current_action.is_some() || !variables.is_empty() {
+					return Err(format!("line {}: variables must come once, before the first action", text_line.number));
+				}
+				
+				text_line.position += 1;
+				
+				if let Err(message) = text_line.expect_symbol(':') {
+					return Err(message);
+				}
+				
+				while !text_line.at_end() {
+					let error: String = text_line.error("hex byte");
+					
+					match text_line.expect_word("hex byte") {
+						Ok(word) => match u8::from_str_radix(&word, 16) {
+							Ok(byte) => variables.push(byte),
+							_ => return Err(error),
+						},
+						
+						Err(message) => return Err(message),
+					}
+				}
+			},
+			
+			"action" => {
+				text_line.position += 1;
+				
+				// The action index is informational
+				if !text_line.accept_symbol(':') {
+					if let Err(message) = text_line.expect_word("action index") {
+						return Err(message);
+					}
+					
+					if let Err(message) = text_line.expect_symbol(':') {
+						return Err(message);
+					}
+				}
+				
+				match parse_action_header(&mut text_line) {
+					Ok(action) => {
+						actions.push(&action);
+						current_action = Some(action);
+					},
+					
+					Err(message) => return Err(message),
+				}
+			},
+			
+			_ => {
+				let instruction: Gd<Instruction>;
+				
+				match parse_instruction(&mut text_line) {
+					Ok(value) => instruction = value,
+					Err(message) => return Err(message),
+				}
+				
+				match current_action.as_mut() {
+					Some(action) => action.bind_mut().instructions.push(&instruction),
+					None => return Err(format!("line {}: instruction outside of an action", text_line.number)),
+				}
+			},
+		}
+	}
+	
+	return Ok((variables, actions));
+}
+
+
+fn parse_action_header(text_line: &mut TextLine) -> Result<Gd<ScriptAction>, String> {
+	let mut flags: u32 = 0;
+	let mut lvflag: u16 = 0;
+	let mut damage: u8 = 0;
+	let mut flag2: u8 = 0;
+	
+	while !text_line.at_end() {
+		let key: String;
+		
+		match text_line.expect_word("action field") {
+			Ok(value) => key = value,
+			Err(message) => return Err(message),
+		}
+		
+		if let Err(message) = text_line.expect_symbol('=') {
+			return Err(message);
+		}
+		
+		let max: i64 = match key.as_str() {
+			"flags" => u32::MAX as i64,
+			"lvflag" => u16::MAX as i64,
+			"damage" | "flag2" => u8::MAX as i64,
+			_ => return Err(format!("line {}: unknown action field '{}'", text_line.number, key)),
+		};
+		
+		let value: i64;
+		
+		match text_line.expect_integer(&key, 0, max) {
+			Ok(integer) => value = integer,
+			Err(message) => return Err(message),
+		}
+		
+		match key.as_str() {
+			"flags" => flags = value as u32,
+			"lvflag" => lvflag = value as u16,
+			"damage" => damage = value as u8,
+			_ => flag2 = value as u8,
+		}
+	}
+	
+	return Ok(Gd::from_init_fn(|base| {
+		ScriptAction {
+			base,
+			flags,
+			lvflag,
+			damage,
+			flag2,
+			instructions: array![],
+		}
+	}));
+}
+
+
+fn parse_instruction(text_line: &mut TextLine) -> Result<Gd<Instruction>, String> {
+	let id: u8;
+	let display_name: String;
+	let mut arguments: Array<Gd<InstructionArgument>> = Array::new();
+	
+	match text_line.expect_integer("instruction ID", 0, u8::MAX as i64) {
+		Ok(value) => id = value as u8,
+		Err(message) => return Err(message),
+	}
+	
+	match text_line.expect_quoted("instruction name") {
+		Ok(value) => display_name = value,
+		Err(message) => return Err(message),
+	}
+	
+	if text_line.accept_symbol('(') {
+		loop {
+			match parse_argument(text_line) {
+				Ok(argument) => arguments.push(&argument),
+				Err(message) => return Err(message),
+			}
+			
+			if text_line.accept_symbol(')') {
+				break;
+			}
+			
+			if let Err(message) = text_line.expect_symbol(',') {
+				return Err(message);
+			}
+		}
+	}
+	
+	if let Err(message) = text_line.expect_end() {
+		return Err(message);
+	}
+	
+	return Ok(Gd::from_init_fn(|base| {
+		Instruction {
+			base,
+			id,
+			display_name: display_name.into(),
+			arguments,
+		}
+	}));
+}
+
+
+// Parses `<type> "<name>" = <value>`, checking the value fits the type.
+fn parse_argument(text_line: &mut TextLine) -> Result<Gd<InstructionArgument>, String> {
+	let error: String = text_line.error("argument type (u8, s8, u16, s16, u32, s32)");
+	let type_name: String;
+	let argument_name: String;
+	let value: i64;
+	
+	match text_line.expect_word("argument type") {
+		Ok(word) => type_name = word,
+		Err(message) => return Err(message),
+	}
+	
+	let (size, signed, min, max): (u8, bool, i64, i64);
+	
+	match argument_type(&type_name) {
+		Some(parsed) => (size, signed, min, max) = parsed,
+		None => return Err(error),
+	}
+	
+	match text_line.expect_quoted("argument name") {
+		Ok(text) => argument_name = text,
+		Err(message) => return Err(message),
+	}
+	
+	if let Err(message) = text_line.expect_symbol('=') {
+		return Err(message);
+	}
+	
+	match text_line.expect_integer(&type_name, min, max) {
+		Ok(integer) => value = integer,
+		Err(message) => return Err(message),
+	}
+	
+	return Ok(Gd::from_init_fn(|base| {
+		InstructionArgument {
+			base,
+			display_name: argument_name.into(),
+			size,
+			value,
+			signed,
+		}
+	}));
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	#[test]
+	fn negative_argument_round_trips() {
+		let bin_data: Vec<u8> = encode_argument(-5, 1);
+		assert_eq!(bin_data, vec![0xFB]);
+		
+		assert_eq!(decode_argument(&bin_data, 1, true), -5);
+		assert_eq!(decode_argument(&bin_data, 1, false), 0xFB);
+		assert_eq!(normalize_argument(0xFB, 1, true), -5);
+		
+		assert_eq!(encode_argument(-2, 2), vec![0xFE, 0xFF]);
+		assert_eq!(decode_argument(&[0xFE, 0xFF], 2, true), -2);
+	}
+	
+	
+	#[test]
+	fn empty_action_is_terminated() {
+		// Same fields and instruction as ScriptAction::new_empty()
+		let end_action: Vec<u8> = encode_instruction(ID_END_ACTION, &[]);
+		let bin_data: Vec<u8> = encode_action(0, 0, 0, 0, &[end_action]);
+		
+		assert_eq!(bin_data.len(), 9);
+		assert_eq!(bin_data.last(), Some(&0xFF));
+	}
+	
+	
+	#[test]
+	fn json_argument_keeps_sign() {
+		let argument: ScriptJSONArgument = ScriptJSONArgument {
+			name: String::from("X Offset"),
+			size: 1,
+			signed: true,
+			value: normalize_argument(0xFB, 1, true),
+		};
+		
+		let json: String = serde_json::to_string(&argument).unwrap();
+		assert!(json.contains("\"value\":-5"));
+		
+		let parsed: ScriptJSONArgument = serde_json::from_str(&json).unwrap();
+		assert_eq!(parsed.value, -5);
+		assert_eq!(encode_argument(parsed.value, parsed.size), vec![0xFB]);
+	}
+	
+	
+	#[test]
+	fn integers_parse() {
+		assert_eq!(parse_integer("12"), Some(12));
+		assert_eq!(parse_integer("-12"), Some(-12));
+		assert_eq!(parse_integer("0x1F"), Some(0x1F));
+		assert_eq!(parse_integer("-0X10"), Some(-0x10));
+		assert_eq!(parse_integer("0xZZ"), None);
+		assert_eq!(parse_integer("twelve"), None);
+	}
+	
+	
+	#[test]
+	fn argument_types_have_ranges() {
+		assert_eq!(argument_type("u8"), Some((1, false, 0, 255)));
+		assert_eq!(argument_type("s8"), Some((1, true, -128, 127)));
+		assert_eq!(argument_type("s16"), Some((2, true, -32768, 32767)));
+		assert_eq!(argument_type("u32"), Some((4, false, 0, 0xFFFFFFFF)));
+		assert_eq!(argument_type("s64"), None);
+	}
 }
\ No newline at end of file
@@ -1,12 +1,18 @@
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::collections::hash_map::DefaultHasher;
+
 use godot::prelude::*;
 use godot::classes::ImageTexture;
 use godot::classes::Image;
 use godot::classes::image::Format;
 
+use crate::bin_palette::BinPalette;
 use crate::sprite_transform;
 use crate::sprite_compress;
 use crate::sprite_compress::CompressedData;
 use crate::sprite_compress::SpriteData;
+use crate::logging::*;
 
 pub const HEADER_SIZE: usize = 16;
 
@@ -89,6 +95,96 @@ pub fn make_header(compressed: bool, clut: u16, bit_depth: u16, width: u16, heig
 }
 
 
+// Number of colors in a sprite's embedded palette, or None if it doesn't fit in the data.
+// Normally 2^bit_depth, but some modded 8bpp sprites only carry 16 colors; detect those by the
+// full palette not fitting. Compressed sprites only reserve room for the iteration count here,
+// so an 8bpp sprite with a 16-color palette and a stream longer than the 240 missing colors
+// (0x3C0 bytes) can't be told apart, and is read as 256 colors.
+pub fn embedded_color_count(bin_data: &[u8], header: &BinHeader) -> Option<usize> {
+	if header.clut != 0x20 {
+		return Some(0);
+	}
+	
+	if header.bit_depth != 4 && header.bit_depth != 8 {
+		return None;
+	}
+	
+	let color_count: usize = 2usize.pow(header.bit_depth as u32);
+	
+	if header.bit_depth != 8 {
+		if HEADER_SIZE + 4 * color_count > bin_data.len() {
+			return None;
+		}
+		
+		return Some(color_count);
+	}
+	
+	// Compressed data needs at least the iteration count, uncompressed the whole pixel array
+	let data_size: usize;
+	
+	if header.compressed {
+		data_size = 0x04;
+	} else {
+		data_size = header.width as usize * header.height as usize;
+	}
+	
+	// Prefer whichever palette leaves room for the pixels, then whichever fits at all
+	for size in [data_size, 0] {
+		if HEADER_SIZE + 4 * color_count + size <= bin_data.len() {
+			return Some(color_count);
+		}
+		
+		if HEADER_SIZE + 4 * 16 + size <= bin_data.len() {
+			log_warn!("Sprite declares 8bpp but only has room for a 16-color palette, reading 16 colors");
+			return Some(16);
+		}
+	}
+	
+	return None;
+}
+
+
+// The header hash +R writes for compressed sprites: every little-endian 16-bit word
+// of the compressed stream XORed together. A trailing odd byte is left out.
+pub fn stream_hash(stream: &[u8]) -> u16 {
+	let mut hash: u16 = 0;
+	
+	for word in stream.chunks_exact(2) {
+		hash = hash ^ (word[0] as u16 | (word[1] as u16) << 8);
+	}
+	
+	return hash;
+}
+
+
+// 64-bit FNV-1a, continuing from `hash`. Unlike DefaultHasher, stable across runs and Rust versions.
+pub const FNV_OFFSET: u64 = 0xCBF29CE484222325;
+
+pub fn fnv_hash(mut hash: u64, bytes: &[u8]) -> u64 {
+	for byte in bytes {
+		hash ^= *byte as u64;
+		hash = hash.wrapping_mul(0x100000001B3);
+	}
+	
+	return hash;
+}
+
+
+// Smallest valid sprite: uncompressed, palette-less, 8bpp, 1x1, aligned to 0x10.
+pub fn make_blank_sprite() -> Vec<u8> {
+	let mut bin_data: Vec<u8> = make_header(false, 0x00, 8, 1, 1, 0, 0, 0);
+	
+	// Single transparent pixel
+	bin_data.push(0x00);
+	
+	while bin_data.len() % 0x10 != 0 {
+		bin_data.push(0xFF);
+	}
+	
+	return bin_data;
+}
+
+
 #[derive(GodotClass)]
 #[class(tool, base=Resource)]
 /// Data resulting from loading a sprite_#.bin file.
@@ -109,6 +205,13 @@ pub struct BinSprite {
 	/// A [PackedByteArray] representing a list of RGBA colors.
 	#[export]
 	pub palette: PackedByteArray,
+	/// Whether the sprite was edited since it was loaded. Sprites that weren't are
+	/// saved as the exact bytes they were loaded from, skipping recompression.
+	#[export]
+	pub dirty: bool,
+	/// The exact bytes this sprite was loaded from, if any.
+	pub source_bin: Vec<u8>,
+	source_hash: u64,
 }
 
 
@@ -122,6 +225,9 @@ impl IResource for BinSprite {
 			texture: None,
 			bit_depth: 8,
 			palette: PackedByteArray::from(vec![]),
+			dirty: true,
+			source_bin: Vec::new(),
+			source_hash: 0,
 		}
 	}
 }
@@ -140,13 +246,137 @@ impl BinSprite {
 				image: Some(image),
 				bit_depth,
 				palette,
+				dirty: true,
+				source_bin: Vec::new(),
+				source_hash: 0,
 			}
 		});
 	}
 	
 	
+	/// Static constructor for BinSprites from a Godot Image, e.g. one captured from a viewport.
+	/// L8 images are used as-is. Other formats take the red channel as the color index,
+	/// same as PNG import, so grayscale previews round-trip exactly.
+	/// `bit_depth` must be 4 or 8; at 4, indices above 15 are clamped.
+	#[func]
+	pub fn from_image(image: Gd<Image>, bit_depth: i64) -> Option<Gd<Self>> {
+		if bit_depth != 4 && bit_depth != 8 {
+			log_error!("BinSprite::from_image() error: Invalid bit depth ({})", bit_depth);
+			return None;
+		}
+		
+		let width: i32 = image.get_width();
+		let height: i32 = image.get_height();
+		
+		if width == 0 || height == 0 {
+			log_error!("BinSprite::from_image() error: Image is empty");
+			return None;
+		}
+		
+		let mut pixels: Vec<u8>;
+		
+		if image.get_format() == Format::L8 {
+			pixels = image.get_data().to_vec();
+		}
+		
+		else {
+			// Convert a copy, leave the caller's image alone
+			let mut working: Gd<Image>;
+			
+			match Image::create_from_data(
+				width, height, image.has_mipmaps(), image.get_format(), &image.get_data()
+			) {
+				Some(copy) => working = copy,
+				None => return None,
+			}
+			
+			working.convert(Format::RGBA8);
+			
+			pixels = working.get_data().to_vec().chunks(4).map(|color| color[0]).collect();
+		}
+		
+		// Drop mipmaps, if any
+		pixels.truncate(width as usize * height as usize);
+		
+		if bit_depth == 4 {
+			pixels = sprite_transform::limit_16_colors(pixels);
+		}
+		
+		let new_image: Gd<Image>;
+		
+		match Image::create_from_data(
+			width, height, false, Format::L8, &PackedByteArray::from(pixels.clone())
+		) {
+			Some(gd_image) => new_image = gd_image,
+			None => return None,
+		}
+		
+		return Some(Self::new_from_data(
+			PackedByteArray::from(pixels), new_image, bit_depth as u16, PackedByteArray::new()
+		));
+	}
+	
+	
+	/// Caches the bytes this sprite was loaded from and marks it as not dirty.
+	pub fn set_source_bin(&mut self, bin_data: Vec<u8>) {
+		self.source_hash = self.data_hash();
+		self.source_bin = bin_data;
+		self.dirty = false;
+	}
+	
+	
+	/// Returns a hash of this sprite's pixels, palette, bit depth and size.
+	/// Stable across sessions, so it can key cached previews.
+	#[func]
+	pub fn content_hash(&self) -> i64 {
+		let mut hash: u64 = FNV_OFFSET;
+		let (width, height): (i32, i32) = self.image.as_ref().map_or((0, 0), |image| (image.get_width(), image.get_height()));
+		
+		hash = fnv_hash(hash, &width.to_le_bytes());
+		hash = fnv_hash(hash, &height.to_le_bytes());
+		hash = fnv_hash(hash, &self.bit_depth.to_le_bytes());
+		hash = fnv_hash(hash, self.pixels.as_slice());
+		hash = fnv_hash(hash, self.palette.as_slice());
+		
+		return hash as i64;
+	}
+	
+	
+	// Hashes everything to_bin encodes, catching edits made without setting dirty.
+	fn data_hash(&self) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		
+		self.pixels.as_slice().hash(&mut hasher);
+		self.palette.as_slice().hash(&mut hasher);
+		self.bit_depth.hash(&mut hasher);
+		
+		match &self.image {
+			Some(image) => {
+				image.get_width().hash(&mut hasher);
+				image.get_height().hash(&mut hasher);
+			},
+			
+			None => (),
+		}
+		
+		return hasher.finish();
+	}
+	
+	
 	pub fn to_bin(&self) -> Vec<u8> {
-		let image = self.image.as_ref().unwrap();
+		// Untouched since load, reuse the original bytes
+		if !self.dirty && !self.source_bin.is_empty() && self.data_hash() == self.source_hash {
+			return self.source_bin.clone();
+		}
+		
+		// Blank sprite (failed load or never filled in), write a placeholder
+		let image: &Gd<Image>;
+		
+		match self.image.as_ref() {
+			Some(value) => image = value,
+			None => return make_blank_sprite(),
+		}
+		
 		let width: u16 = image.get_width() as u16;
 		let height: u16 = image.get_height() as u16;
 	
@@ -161,14 +391,7 @@ impl BinSprite {
 		let compressed_data: CompressedData = sprite_compress::compress(sprite_data);
 		
 		// Generate hash
-		let mut hash: u16 = 0;
-		
-		for byte in 0..compressed_data.stream.len() / 2 {
-			hash = hash ^ (
-				(compressed_data.stream[byte + 0] as u16) |
-				(compressed_data.stream[byte + 1] as u16) << 8
-			);
-		}
+		let hash: u16 = stream_hash(&compressed_data.stream);
 		
 		// Construct header
 		let header: Vec<u8> = make_header(
@@ -183,31 +406,205 @@ impl BinSprite {
 		);
 		
 		let mut bin_data: Vec<u8> = Vec::new();
-		let iterations_u32: u32 = compressed_data.iterations as u32;
 		
 		bin_data.extend(header);
 		bin_data.extend(self.palette.to_vec());
-		bin_data.extend_from_slice(&[
-			(iterations_u32 >> 16) as u8,	// BB
-			(iterations_u32 >> 24) as u8,	// AA
-			(iterations_u32 >> 00) as u8,	// DD
-			(iterations_u32 >> 08) as u8,	// CC
-		]);
+		bin_data.extend(sprite_compress::compressed_to_bin(&compressed_data));
+		
+		return bin_data;
+	}
+	
+	
+	/// Compresses and decompresses this sprite, returning true if its pixels survive unchanged.
+	#[func]
+	pub fn verify_compression(&self) -> bool {
+		let image: &Gd<Image>;
 		
-		for byte in 0..compressed_data.stream.len() / 2 {
-			bin_data.extend([
-				compressed_data.stream[2 * byte + 1],
-				compressed_data.stream[2 * byte + 0],
-			]);
+		match self.image.as_ref() {
+			Some(value) => image = value,
+			None => return true,
 		}
 		
-		return bin_data;
+		return sprite_compress::verify_round_trip(&SpriteData {
+			width: image.get_width() as u16,
+			height: image.get_height() as u16,
+			bit_depth: self.bit_depth,
+			pixels: self.pixels.to_vec(),
+			palette: self.palette.to_vec(),
+		});
+	}
+	
+	
+	/// Returns how many times each of the 256 color indices is used by this sprite.
+	#[func]
+	pub fn index_histogram(&self) -> PackedInt64Array {
+		let mut histogram: Vec<i64> = vec![0; 256];
+		
+		for pixel in self.pixels.as_slice() {
+			histogram[*pixel as usize] += 1;
+		}
+		
+		return PackedInt64Array::from(histogram);
+	}
+	
+	
+	/// Returns whether every pixel fits in the given color depth (4 or 8).
+	#[func]
+	pub fn fits_bit_depth(&self, bit_depth: u16) -> bool {
+		if bit_depth >= 8 {
+			return true;
+		}
+		
+		let max_index: u8 = (2u16.pow(bit_depth as u32) - 1) as u8;
+		return self.pixels.as_slice().iter().all(|pixel| *pixel <= max_index);
+	}
+	
+	
+	/// Changes the sprite's color depth to 4 or 8. When demoting, pixels above index 15
+	/// are clamped (check with fits_bit_depth first). Embedded palettes are padded or
+	/// truncated to the new color count. Returns false for invalid depths.
+	#[func]
+	pub fn set_bit_depth(&mut self, bit_depth: u16) -> bool {
+		if bit_depth != 4 && bit_depth != 8 {
+			return false;
+		}
+		
+		if bit_depth == self.bit_depth {
+			return true;
+		}
+		
+		if bit_depth == 4 {
+			self.pixels = sprite_transform::limit_16_colors(self.pixels.to_vec()).into();
+		}
+		
+		if !self.palette.is_empty() {
+			let mut palette: Vec<u8> = self.palette.to_vec();
+			palette.resize(4 * 2usize.pow(bit_depth as u32), 0u8);
+			self.palette = palette.into();
+		}
+		
+		self.bit_depth = bit_depth;
+		self.dirty = true;
+		
+		// Clamping may have changed the pixels, rebuild preview
+		match Image::create_from_data(
+			self.image.as_ref().map_or(0, |image| image.get_width()),
+			self.image.as_ref().map_or(0, |image| image.get_height()),
+			// Mipmapping
+			false,
+			// Grayscale format
+			Format::L8,
+			// Pixel array
+			&self.pixels
+		) {
+			Some(new_image) => {
+				self.texture = ImageTexture::create_from_image(&new_image);
+				self.image = Some(new_image);
+			},
+			
+			_ => (),
+		}
+		
+		return true;
+	}
+	
+	
+	/// Returns the sprite's embedded palette as a [BinPalette], or null if it has none.
+	#[func]
+	pub fn get_palette(&self) -> Option<Gd<BinPalette>> {
+		if self.palette.is_empty() {
+			return None;
+		}
+		
+		let mut bin_palette: Gd<BinPalette> = BinPalette::new_gd();
+		bin_palette.bind_mut().palette = self.palette.clone();
+		return Some(bin_palette);
+	}
+	
+	
+	/// Reassigns every pixel to the color in `target` closest to its current color, by
+	/// squared RGB distance, then replaces the embedded palette with `target`.
+	/// Index 0 stays transparent and no other pixel is mapped to it. 4bpp sprites only
+	/// use the first 16 colors of `target`. Requires an embedded palette.
+	#[func]
+	pub fn remap_to_palette(&mut self, target: PackedByteArray) {
+		let color_count: usize = std::cmp::min(target.len() / 4, 2usize.pow(self.bit_depth as u32));
+		
+		if self.palette.is_empty() {
+			log_error!("BinSprite::remap_to_palette() error: Sprite has no embedded palette");
+			return;
+		}
+		
+		if color_count < 2 {
+			log_error!("BinSprite::remap_to_palette() error: Target palette needs at least 2 colors");
+			return;
+		}
+		
+		let source: &[u8] = self.palette.as_slice();
+		let target_colors: &[u8] = target.as_slice();
+		let mut index_map: Vec<u8> = vec![0; 256];
+		
+		for index in 1..256 {
+			// Indices past the palette read as black
+			let mut color: [i64; 3] = [0; 3];
+			
+			if 4 * index + 3 < source.len() {
+				for channel in 0..3 {
+					color[channel] = source[4 * index + channel] as i64;
+				}
+			}
+			
+			let mut best_distance: i64 = i64::MAX;
+			
+			for candidate in 1..color_count {
+				let mut distance: i64 = 0;
+				
+				for channel in 0..3 {
+					let difference: i64 = target_colors[4 * candidate + channel] as i64 - color[channel];
+					distance += difference * difference;
+				}
+				
+				if distance < best_distance {
+					best_distance = distance;
+					index_map[index] = candidate as u8;
+				}
+			}
+		}
+		
+		let new_pixels: Vec<u8> = self.pixels.as_slice().iter().map(|pixel| index_map[*pixel as usize]).collect();
+		let mut new_palette: Vec<u8> = target.to_vec();
+		new_palette.resize(4 * 2usize.pow(self.bit_depth as u32), 0u8);
+		
+		self.pixels = new_pixels.into();
+		self.palette = new_palette.into();
+		self.dirty = true;
+		
+		// Reconstruct image for preview in Godot
+		match Image::create_from_data(
+			self.image.as_ref().map_or(0, |image| image.get_width()),
+			self.image.as_ref().map_or(0, |image| image.get_height()),
+			// Mipmapping
+			false,
+			// Grayscale format
+			Format::L8,
+			// Pixel array
+			&self.pixels
+		) {
+			Some(new_image) => {
+				self.texture = ImageTexture::create_from_image(&new_image);
+				self.image = Some(new_image);
+			},
+			
+			_ => (),
+		}
 	}
 	
 	
 	/// Reindexing function. Reorders colors from 1-2-3-4 to 1-3-2-4 and vice-versa.
 	#[func]
 	pub fn reindex(&mut self) {
+		self.dirty = true;
+		
 		let new_pixels: Vec<u8> = sprite_transform::reindex_vector(self.pixels.to_vec());
 		self.pixels = new_pixels.into();
 		
@@ -235,4 +632,62 @@ impl BinSprite {
 		self.image = Some(new_image.clone());
 		self.texture = Some(ImageTexture::create_from_image(&new_image).unwrap());
 	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	// Header for a 4x4 uncompressed sprite with an embedded palette, padded out to `length` bytes.
+	fn sprite_bin(bit_depth: u16, length: usize) -> Vec<u8> {
+		let mut bin_data: Vec<u8> = make_header(false, 0x20, bit_depth, 4, 4, 0, 0, 0);
+		bin_data.resize(length, 0x00);
+		return bin_data;
+	}
+	
+	
+	fn color_count(bin_data: &Vec<u8>) -> Option<usize> {
+		return embedded_color_count(bin_data, &get_header(bin_data[0x00..0x10].to_vec()));
+	}
+	
+	
+	#[test]
+	fn full_palette() {
+		assert_eq!(color_count(&sprite_bin(8, HEADER_SIZE + 4 * 256 + 16)), Some(256));
+		assert_eq!(color_count(&sprite_bin(4, HEADER_SIZE + 4 * 16 + 8)), Some(16));
+	}
+	
+	
+	#[test]
+	fn no_palette() {
+		let mut bin_data: Vec<u8> = make_header(false, 0x00, 8, 4, 4, 0, 0, 0);
+		bin_data.resize(HEADER_SIZE + 16, 0x00);
+		assert_eq!(color_count(&bin_data), Some(0));
+	}
+	
+	
+	#[test]
+	fn short_8bpp_palette_is_recovered() {
+		assert_eq!(color_count(&sprite_bin(8, HEADER_SIZE + 4 * 16 + 16)), Some(16));
+	}
+	
+	
+	#[test]
+	fn truncated_palette_is_rejected() {
+		assert_eq!(color_count(&sprite_bin(4, 0x30)), None);
+		assert_eq!(color_count(&sprite_bin(8, 0x30)), None);
+	}
+	
+	
+	#[test]
+	fn stream_hash_xors_every_word() {
+		// 0x0201 ^ 0x0403, hashing overlapping pairs would give 0x0103
+		assert_eq!(stream_hash(&[0x01, 0x02, 0x03, 0x04]), 0x0602);
+		
+		// The odd byte at the end is not hashed
+		assert_eq!(stream_hash(&[0x34, 0x12, 0x00, 0xFF, 0x77]), 0xED34);
+		assert_eq!(stream_hash(&[]), 0x0000);
+	}
 }
\ No newline at end of file
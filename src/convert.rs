@@ -0,0 +1,129 @@
+use std::io::BufWriter;
+use std::fs;
+use std::fs::File;
+use std::path::PathBuf;
+
+use crate::sprite_get;
+use crate::sprite_compress;
+use crate::sprite_transform;
+use crate::logging::*;
+
+use sprite_compress::SpriteData;
+
+
+// Godot-free conversion functions. Nothing in here may construct Godot types,
+// so these can be used from headless tools and batch jobs.
+
+
+/// Decodes a sprite .bin file and writes it out as an indexed .png file.
+/// The sprite's embedded palette is used if present, otherwise `palette` is used.
+/// Colors missing from both are filled with a grayscale ramp.
+pub fn convert_bin_to_png(source_file: &PathBuf, target_file: &PathBuf, palette: &[u8]) -> bool {
+	let sprite_data: SpriteData;
+	
+	match sprite_get::get_bin(source_file) {
+		Ok(data) => sprite_data = data,
+		Err(_) => return false,
+	}
+	
+	if sprite_data.width == 0 || sprite_data.height == 0 {
+		log_error!("convert::convert_bin_to_png() error: Sprite is empty");
+		log_error!("\tSkipped: {}", &source_file.display());
+		return false;
+	}
+	
+	return write_png(target_file, &sprite_data, palette);
+}
+
+
+/// Writes decoded sprite data out as an indexed .png file.
+pub fn write_png(target_file: &PathBuf, sprite_data: &SpriteData, palette: &[u8]) -> bool {
+	let mut directory: PathBuf = target_file.clone();
+	let _ = directory.pop();
+	let _ = fs::create_dir_all(&directory);
+	
+	let png_file: File;
+	match File::create(target_file) {
+		Ok(file) => png_file = file,
+		_ => {
+			log_error!("convert::write_png() error: Could not create PNG file");
+			log_error!("\tSkipped: {}", &target_file.display());
+			return false;
+		},
+	}
+	
+	let width: u32 = sprite_data.width as u32;
+	let height: u32 = sprite_data.height as u32;
+	
+	let ref mut buffer = BufWriter::new(png_file);
+	let mut encoder = png::Encoder::new(buffer, width, height);
+	
+	// 4 bpp handling
+	let working_pixels: Vec<u8>;
+	
+	match sprite_data.bit_depth {
+		4 => {
+			let aligned: Vec<u8> = sprite_transform::align_to_4(sprite_data.pixels.clone(), height as usize);
+			working_pixels = sprite_transform::bpp_to_4(aligned, false);
+			encoder.set_depth(png::BitDepth::Four);
+		},
+		
+		8 => {
+			working_pixels = sprite_data.pixels.clone();
+			encoder.set_depth(png::BitDepth::Eight);
+		},
+		
+		_ => {
+			log_error!("convert::write_png() error: Invalid bit depth ({})", sprite_data.bit_depth);
+			return false;
+		},
+	}
+	
+	encoder.set_color(png::ColorType::Indexed);
+	
+	// Palette
+	let color_count: usize = 2usize.pow(sprite_data.bit_depth as u32);
+	let source_palette: &[u8];
+	
+	if sprite_data.palette.is_empty() {
+		source_palette = palette;
+	} else {
+		source_palette = &sprite_data.palette;
+	}
+	
+	let mut rgb_palette: Vec<u8> = Vec::with_capacity(color_count * 3);
+	let mut trns_chunk: Vec<u8> = Vec::with_capacity(color_count);
+	
+	for index in 0..color_count {
+		if 4 * index + 3 < source_palette.len() {
+			rgb_palette.push(source_palette[4 * index + 0]);
+			rgb_palette.push(source_palette[4 * index + 1]);
+			rgb_palette.push(source_palette[4 * index + 2]);
+			trns_chunk.push(source_palette[4 * index + 3]);
+		}
+		
+		else {
+			let gray: u8 = (index * 255 / (color_count - 1)) as u8;
+			rgb_palette.push(gray);
+			rgb_palette.push(gray);
+			rgb_palette.push(gray);
+			trns_chunk.push(0xFF);
+		}
+	}
+	
+	encoder.set_palette(rgb_palette);
+	encoder.set_trns(trns_chunk);
+	
+	match encoder.write_header() {
+		Ok(mut writer) => match writer.write_image_data(&working_pixels) {
+			Ok(_) => return true,
+			_ => (),
+		},
+		
+		_ => (),
+	}
+	
+	log_error!("convert::write_png() error: PNG encoding failed");
+	log_error!("\tFile: {}", &target_file.display());
+	return false;
+}
@@ -17,8 +17,23 @@ pub mod sprite_transform;
 
 // Generic
 pub mod sort;
+pub mod convert;
+pub mod logging;
+pub mod audio_vag;
 
 struct GGPRBin;
 
 #[gdextension]
-unsafe impl ExtensionLibrary for GGPRBin {}
\ No newline at end of file
+unsafe impl ExtensionLibrary for GGPRBin {
+	fn on_level_init(level: InitLevel) {
+		if level == InitLevel::Scene {
+			logging::set_godot_ready(true);
+		}
+	}
+	
+	fn on_level_deinit(level: InitLevel) {
+		if level == InitLevel::Scene {
+			logging::set_godot_ready(false);
+		}
+	}
+}
\ No newline at end of file
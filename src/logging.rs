@@ -0,0 +1,136 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
+
+use godot::prelude::*;
+use godot::classes::Engine;
+
+
+// Message levels, from least to most verbose.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+	Error = 0,
+	Warn = 1,
+	Info = 2,
+	Debug = 3,
+}
+
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+// Set once the extension is loaded. Before that (or in headless tools), print to stdout.
+static GODOT_READY: AtomicBool = AtomicBool::new(false);
+// Name the BinLog instance is registered under, so scripts can connect to its signal.
+const SINGLETON_NAME: &str = "BinLog";
+
+
+pub fn set_godot_ready(ready: bool) {
+	GODOT_READY.store(ready, Ordering::Relaxed);
+	
+	if ready {
+		Engine::singleton().register_singleton(SINGLETON_NAME, &BinLog::new_alloc());
+	} else {
+		match Engine::singleton().get_singleton(SINGLETON_NAME) {
+			Some(singleton) => {
+				Engine::singleton().unregister_singleton(SINGLETON_NAME);
+				singleton.free();
+			},
+			
+			None => (),
+		}
+	}
+}
+
+
+pub fn log(level: Level, message: &str) {
+	if level as u8 > LOG_LEVEL.load(Ordering::Relaxed) {
+		return;
+	}
+	
+	if GODOT_READY.load(Ordering::Relaxed) {
+		godot_print!("{}", message);
+		
+		match Engine::singleton().get_singleton(SINGLETON_NAME) {
+			Some(mut singleton) => {
+				singleton.emit_signal("log_message", &[(level as i64).to_variant(), message.to_variant()]);
+			},
+			
+			None => (),
+		}
+	} else {
+		println!("{}", message);
+	}
+}
+
+
+macro_rules! log_error {
+	($($arg:tt)*) => { $crate::logging::log($crate::logging::Level::Error, &format!($($arg)*)) };
+}
+
+
+macro_rules! log_warn {
+	($($arg:tt)*) => { $crate::logging::log($crate::logging::Level::Warn, &format!($($arg)*)) };
+}
+
+
+macro_rules! log_info {
+	($($arg:tt)*) => { $crate::logging::log($crate::logging::Level::Info, &format!($($arg)*)) };
+}
+
+
+macro_rules! log_debug {
+	($($arg:tt)*) => { $crate::logging::log($crate::logging::Level::Debug, &format!($($arg)*)) };
+}
+
+
+pub(crate) use log_error;
+pub(crate) use log_warn;
+pub(crate) use log_info;
+#[allow(unused_imports)]
+pub(crate) use log_debug;
+
+
+#[derive(GodotClass)]
+#[class(tool, base=Object)]
+/// Controls how much the extension prints to the console.
+/// Registered as the BinLog engine singleton, which emits [signal log_message].
+pub struct BinLog {
+	base: Base<Object>,
+}
+
+
+#[godot_api]
+impl IObject for BinLog {
+	fn init(base: Base<Object>) -> Self {
+		Self {
+			base,
+		}
+	}
+}
+
+
+#[godot_api]
+impl BinLog {
+	#[constant] const LEVEL_ERROR: i64 = 0;
+	#[constant] const LEVEL_WARN: i64 = 1;
+	#[constant] const LEVEL_INFO: i64 = 2;
+	#[constant] const LEVEL_DEBUG: i64 = 3;
+	
+	
+	/// Emitted for every message that passes the log level, once the extension is loaded.
+	/// `level` is one of the LEVEL_* constants.
+	#[signal]
+	fn log_message(level: i64, message: GString);
+	
+	
+	/// Sets the most verbose level of message that gets printed. Defaults to LEVEL_INFO.
+	/// For example, LEVEL_WARN hides per-file notes during bulk imports.
+	#[func] pub fn set_log_level(level: i64) {
+		LOG_LEVEL.store(level.clamp(Self::LEVEL_ERROR, Self::LEVEL_DEBUG) as u8, Ordering::Relaxed);
+	}
+	
+	
+	/// Returns the current log level.
+	#[func] pub fn get_log_level() -> i64 {
+		return LOG_LEVEL.load(Ordering::Relaxed) as i64;
+	}
+}
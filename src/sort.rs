@@ -4,6 +4,8 @@ use std::fs::ReadDir;
 
 use godot::prelude::*;
 
+use crate::logging::*;
+
 
 #[derive(GodotClass)]
 #[class(tool, base=Resource)]
@@ -30,7 +32,7 @@ impl FileSort {
 		let path_buf: PathBuf = PathBuf::from(path.to_string());
 		
 		if !path_buf.exists() {
-			godot_print!("sort::FileSort::get_sorted_files() error: Could not find directory!");
+			log_error!("sort::FileSort::get_sorted_files() error: Could not find directory!");
 			return Default::default();
 		}
 		
@@ -2,6 +2,7 @@ use std::io::Cursor;
 use std::cmp::min;
 use bitstream_io::{BitReader, BitRead, BitWriter, BitWrite, BigEndian};
 use crate::{
+	bin_sprite,
 	bin_sprite::BinHeader,
 	sprite_transform,
 };
@@ -35,6 +36,73 @@ impl Default for SpriteData {
 }
 
 
+// Iteration counts are stored as BB AA DD CC (for 0xAABBCCDD).
+pub fn encode_iterations(iterations: u32) -> [u8; 4] {
+	return [
+		(iterations >> 16) as u8,	// BB
+		(iterations >> 24) as u8,	// AA
+		(iterations >> 00) as u8,	// DD
+		(iterations >> 08) as u8,	// CC
+	];
+}
+
+
+pub fn decode_iterations(bytes: [u8; 4]) -> u32 {
+	return u32::from_le_bytes([bytes[2], bytes[3], bytes[0], bytes[1]]);
+}
+
+
+// Returns the bytes following a compressed sprite's header and palette:
+// the iteration count, then the bit stream with each 16-bit word byte-swapped.
+pub fn compressed_to_bin(compressed_data: &CompressedData) -> Vec<u8> {
+	let mut bin_data: Vec<u8> = Vec::with_capacity(compressed_data.stream.len() + 4);
+	
+	bin_data.extend_from_slice(&encode_iterations(compressed_data.iterations as u32));
+	
+	for byte in 0..compressed_data.stream.len() / 2 {
+		bin_data.extend([
+			compressed_data.stream[2 * byte + 1],
+			compressed_data.stream[2 * byte + 0],
+		]);
+	}
+	
+	return bin_data;
+}
+
+
+// Compresses then decompresses the sprite, returning true if the pixels survive intact.
+// Catches the compressor and decompressor drifting apart on the stream format.
+pub fn verify_round_trip(data: &SpriteData) -> bool {
+	let pixel_count: usize = data.width as usize * data.height as usize;
+	
+	let compressed_data: CompressedData = compress(SpriteData {
+		width: data.width,
+		height: data.height,
+		bit_depth: data.bit_depth,
+		pixels: data.pixels.clone(),
+		palette: data.palette.clone(),
+	});
+	
+	let mut bin_data: Vec<u8> = bin_sprite::make_header(
+		true, 0x20 * (!data.palette.is_empty()) as u16, data.bit_depth, data.width, data.height, 0, 0, 0
+	);
+	bin_data.extend(&data.palette);
+	bin_data.extend(compressed_to_bin(&compressed_data));
+	
+	let header: BinHeader = bin_sprite::get_header(bin_data[0x00..0x10].to_vec());
+	let decompressed: SpriteData;
+	
+	match decompress(&bin_data, header) {
+		Some(value) => decompressed = value,
+		None => return false,
+	}
+	
+	return decompressed.pixels.len() == pixel_count
+		&& data.pixels.len() >= pixel_count
+		&& decompressed.pixels[..] == data.pixels[..pixel_count];
+}
+
+
 pub fn compress(mut data: SpriteData) -> CompressedData {
 	// Bit depth management
 	match data.bit_depth {
@@ -143,14 +211,25 @@ pub fn compress(mut data: SpriteData) -> CompressedData {
 }
 
 
-pub fn decompress(bin_data: &Vec<u8>, header: BinHeader) -> SpriteData {
+// Returns None if the data is truncated or malformed: a bad bit depth, a stream that
+// runs dry before `iterations` completes, or a token reaching past the decoded pixels.
+pub fn decompress(bin_data: &Vec<u8>, header: BinHeader) -> Option<SpriteData> {
 	let pixel_count: usize = header.width as usize * header.height as usize;
 	let mut pointer: usize = 0x10;
 	let mut palette: Vec<u8> = Vec::new();
 	
+	if header.bit_depth != 4 && header.bit_depth != 8 {
+		return None;
+	}
+	
 	// Get embedded palette
 	if header.clut == 0x20 {
-		let color_count: usize = 2u16.pow(header.bit_depth as u32) as usize;
+		let color_count: usize;
+		
+		match bin_sprite::embedded_color_count(bin_data, &header) {
+			Some(value) => color_count = value,
+			None => return None,
+		}
 		
 		// Get palette
 		for index in 0..color_count {
@@ -162,14 +241,21 @@ pub fn decompress(bin_data: &Vec<u8>, header: BinHeader) -> SpriteData {
 		}
 		
 		pointer += color_count * 4;
+		
+		// Short palettes are padded out to the full bit depth
+		palette.resize(4 * 2usize.pow(header.bit_depth as u32), 0x00);
+	}
+	
+	if bin_data.len() < pointer + 0x04 {
+		return None;
 	}
 	
 	// Read iterations
-	let iterations: u32 = u32::from_le_bytes([
-		bin_data[pointer + 0x02],
-		bin_data[pointer + 0x03],
+	let iterations: u32 = decode_iterations([
 		bin_data[pointer + 0x00],
-		bin_data[pointer + 0x01]
+		bin_data[pointer + 0x01],
+		bin_data[pointer + 0x02],
+		bin_data[pointer + 0x03]
 	]);
 	
 	// Move pointer past iterations
@@ -190,13 +276,26 @@ pub fn decompress(bin_data: &Vec<u8>, header: BinHeader) -> SpriteData {
 	let mut pixel_vector: Vec<u8> = Vec::new();
 	
 	for _i in 0..iterations {
+		let literal: bool;
+		
+		match bit_reader.read_bit() {
+			Ok(value) => literal = value,
+			_ => return None,
+		}
+		
 		// Literal mode
-		if bit_reader.read_bit().unwrap() == true {
-			pixel_vector.push(bit_reader.read(8).unwrap());
+		if literal {
+			match bit_reader.read::<u8>(8) {
+				Ok(value) => pixel_vector.push(value),
+				_ => return None,
+			}
 			
-			// Stray byte guard rail
-			if pixel_vector.len() + 1 < pixel_count {
-				pixel_vector.push(bit_reader.read(8).unwrap());
+			// Stray byte guard rail, only skip the pad byte once the image is full
+			if pixel_vector.len() < pixel_count {
+				match bit_reader.read::<u8>(8) {
+					Ok(value) => pixel_vector.push(value),
+					_ => return None,
+				}
 			}
 		}
 		
@@ -207,8 +306,22 @@ pub fn decompress(bin_data: &Vec<u8>, header: BinHeader) -> SpriteData {
 				window_origin = pixel_vector.len() - 512;
 			}
 			
-			let offset: usize = bit_reader.read::<u16>(9).unwrap() as usize;
-			let length: usize = 3 + bit_reader.read::<u8>(7).unwrap() as usize;
+			let offset: usize;
+			let length: usize;
+			
+			match (bit_reader.read::<u16>(9), bit_reader.read::<u8>(7)) {
+				(Ok(token_offset), Ok(token_length)) => {
+					offset = token_offset as usize;
+					length = 3 + token_length as usize;
+				},
+				
+				_ => return None,
+			}
+			
+			// Tokens may overlap what they write, but not start past it
+			if window_origin + offset >= pixel_vector.len() {
+				return None;
+			}
 			
 			for pixel in 0..length {
 				pixel_vector.push(pixel_vector[window_origin + offset + pixel]);
@@ -217,20 +330,102 @@ pub fn decompress(bin_data: &Vec<u8>, header: BinHeader) -> SpriteData {
 	}
 	
 	// Bit depth management
-	match header.bit_depth {
-		4 => pixel_vector = sprite_transform::bpp_from_4(pixel_vector, true),
-		8 => (),
-		// Shouldn't ever happen
-		_ => panic!("sprite_compress::decompress() error: Invalid BIN bit depth"),
+	if header.bit_depth == 4 {
+		pixel_vector = sprite_transform::bpp_from_4(pixel_vector, true);
 	}
 	
 	pixel_vector.resize(header.width as usize * header.height as usize, 0u8);
 
-	return SpriteData {
+	return Some(SpriteData {
 		width: header.width,
 		height: header.height,
 		bit_depth: header.bit_depth,
 		pixels: pixel_vector,
 		palette,
-	};
+	});
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	// Small xorshift generator, so the random images are the same on every run.
+	struct TestRng {
+		state: u64,
+	}
+	
+	
+	impl TestRng {
+		fn next(&mut self) -> u64 {
+			self.state ^= self.state << 13;
+			self.state ^= self.state >> 7;
+			self.state ^= self.state << 17;
+			return self.state;
+		}
+		
+		
+		fn below(&mut self, max: u64) -> u64 {
+			return self.next() % max;
+		}
+	}
+	
+	
+	#[test]
+	fn iterations_round_trip() {
+		assert_eq!(decode_iterations(encode_iterations(0xAABBCCDD)), 0xAABBCCDD);
+	}
+	
+	
+	#[test]
+	fn iterations_byte_order() {
+		assert_eq!(encode_iterations(0xAABBCCDD), [0xBB, 0xAA, 0xDD, 0xCC]);
+	}
+	
+	
+	#[test]
+	fn random_sprites_round_trip() {
+		let mut rng: TestRng = TestRng { state: 0x9E3779B97F4A7C15 };
+		
+		for case in 0..64 {
+			let bit_depth: u16 = if case % 2 == 0 { 4 } else { 8 };
+			let width: u16 = 1 + rng.below(48) as u16;
+			let height: u16 = 1 + rng.below(48) as u16;
+			
+			// Few distinct values make for repeats, exercising back references as well as literals
+			let alphabet: u64 = match rng.below(3) {
+				0 => 2,
+				1 => 16,
+				_ => 1 << bit_depth,
+			};
+			
+			let mut pixels: Vec<u8> = Vec::new();
+			
+			for _pixel in 0..width as usize * height as usize {
+				pixels.push(rng.below(alphabet) as u8);
+			}
+			
+			let mut palette: Vec<u8> = Vec::new();
+			
+			if rng.below(2) == 0 {
+				for _byte in 0..4 * (1usize << bit_depth) {
+					palette.push(rng.below(256) as u8);
+				}
+			}
+			
+			let data: SpriteData = SpriteData {
+				width,
+				height,
+				bit_depth,
+				pixels,
+				palette,
+			};
+			
+			assert!(
+				verify_round_trip(&data),
+				"case {}: {}x{} {}bpp, {} colors", case, width, height, bit_depth, alphabet
+			);
+		}
+	}
 }
\ No newline at end of file
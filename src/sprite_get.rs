@@ -7,6 +7,7 @@ use bmp_rust::bmp::{BMP, BITMAPFILEHEADER, DIBHEADER};
 use crate::bin_sprite;
 use crate::sprite_compress;
 use crate::sprite_transform;
+use crate::logging::*;
 
 use bin_sprite::BinHeader;
 use sprite_compress::SpriteData;
@@ -14,50 +15,85 @@ use sprite_compress::SpriteData;
 const BITMAPCOREHEADER_SIZE: usize = 12;
 const BMP_COLOR_24: usize = 3;
 const BMP_COLOR_32: usize = 4;
+const TGA_HEADER_SIZE: usize = 18;
 
+/// File extensions get_sprite_file() can read.
+pub const SPRITE_EXTENSIONS: [&str; 5] = ["png", "raw", "bin", "bmp", "tga"];
 
-pub fn get_sprite_file(source_file: &PathBuf) -> Option<SpriteData> {
+
+pub fn get_sprite_file(source_file: &PathBuf) -> Result<SpriteData, String> {
 	match source_file.extension() {
 		Some(os_str) => match os_str.to_ascii_lowercase().to_str() {
 			Some("png") => return get_png(source_file),
 			Some("raw") => return get_raw(source_file),
 			Some("bin") => return get_bin(source_file),
 			Some("bmp") => return get_bmp(source_file),
+			Some("tga") => return get_tga(source_file),
 			_ => {
-				println!("sprite_import_export::import_sprites() error: Invalid source format provided");
-				return None;
+				log_error!("sprite_import_export::import_sprites() error: Invalid source format provided");
+				return Err(String::from("Unsupported file format"));
 			},
 		},
 		
 		_ => {
-			println!("sprite_import_export::import_sprites() error: Invalid source format provided");
-			return None;
+			log_error!("sprite_import_export::import_sprites() error: Invalid source format provided");
+			return Err(String::from("Unsupported file format"));
 		}
 	}
 }
 
 
-pub fn get_png(source_file: &PathBuf) -> Option<SpriteData> {
+pub fn get_png(source_file: &PathBuf) -> Result<SpriteData, String> {
+	return read_png(source_file, 0x80);
+}
+
+
+// As get_png(), but colors missing from tRNS are opaque (0xFF), as the PNG spec has it.
+// Exported again with make_png() unchanged, such a palette comes out byte-identical.
+pub fn get_png_opaque(source_file: &PathBuf) -> Result<SpriteData, String> {
+	return read_png(source_file, 0xFF);
+}
+
+
+// `missing_alpha` is used for palette colors past the end of the tRNS chunk.
+fn read_png(source_file: &PathBuf, missing_alpha: u8) -> Result<SpriteData, String> {
 	// Get info
 	let file: File;
 	match File::open(&source_file) {
 		Ok(value) => file = value,
 		_ => {
-			println!("sprite_get::get_png() error: PNG file open error");
-			println!("\tSkipped: {}", &source_file.display());
-			return None;
+			log_error!("sprite_get::get_png() error: PNG file open error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not open file"));
 		},
 	}
 	
 	let mut decoder = png::Decoder::new(file);
 	decoder.set_transformations(png::Transformations::STRIP_16);
-	let mut reader = decoder.read_info().unwrap();
+	
+	let mut reader;
+	match decoder.read_info() {
+		Ok(value) => reader = value,
+		_ => {
+			log_error!("sprite_get::get_png() error: PNG header read error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not read PNG header"));
+		},
+	}
 	
 	let mut palette: Vec<u8> = Vec::new();
 	
 	// Get bytes
 	let mut buffer = vec![0; reader.output_buffer_size()];
-	let frame = reader.next_frame(&mut buffer).unwrap();
+	let frame: png::OutputInfo;
+	match reader.next_frame(&mut buffer) {
+		Ok(value) => frame = value,
+		_ => {
+			log_error!("sprite_get::get_png() error: PNG data read error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not decode PNG data"));
+		},
+	}
 	
 	let source_bytes: Vec<u8> = buffer[..frame.buffer_size()].to_vec();
 	let mut pixel_vector: Vec<u8> = Vec::new();
@@ -85,7 +121,7 @@ pub fn get_png(source_file: &PathBuf) -> Option<SpriteData> {
 						_ => (),
 					}
 					
-					alpha_vec.resize(color_count, 0x80);
+					alpha_vec.resize(color_count, missing_alpha);
 					palette = vec![0; color_count * 4];
 					
 					for index in 0..color_count {
@@ -101,24 +137,24 @@ pub fn get_png(source_file: &PathBuf) -> Option<SpriteData> {
 		}
 		
 		png::ColorType::GrayscaleAlpha => {
-			println!("Note: PNG has color type grayscale with alpha, will discard alpha");
-			println!("\tFile: {}", &source_file.display());
+			log_info!("Note: PNG has color type grayscale with alpha, will discard alpha");
+			log_info!("\tFile: {}", &source_file.display());
 			for pixel in 0..source_bytes.len() / 2 {
 				pixel_vector.push(source_bytes[pixel * 2]);
 			}
 		},
 		
 		png::ColorType::Rgb => {
-			println!("Note: PNG has color type RGB, will use red channel as grayscale");
-			println!("\tFile: {}", &source_file.display());
+			log_info!("Note: PNG has color type RGB, will use red channel as grayscale");
+			log_info!("\tFile: {}", &source_file.display());
 			for pixel in 0..source_bytes.len() / 3 {
 				pixel_vector.push(source_bytes[pixel * 3]);
 			}
 		},
 		
 		png::ColorType::Rgba => {
-			println!("Note: PNG has color type RGBA, will use red channel as grayscale and discard alpha");
-			println!("\tFile: {}", &source_file.display());
+			log_info!("Note: PNG has color type RGBA, will use red channel as grayscale and discard alpha");
+			log_info!("\tFile: {}", &source_file.display());
 			for pixel in 0..source_bytes.len() / 4 {
 				pixel_vector.push(source_bytes[pixel * 4]);
 			}
@@ -146,7 +182,7 @@ pub fn get_png(source_file: &PathBuf) -> Option<SpriteData> {
 		_ => (),	// Hope and pray
 	}
 
-	return Some(
+	return Ok(
 		SpriteData {
 			width: reader.info().width as u16,
 			height: reader.info().height as u16,
@@ -158,7 +194,77 @@ pub fn get_png(source_file: &PathBuf) -> Option<SpriteData> {
 }
 
 
-pub fn get_raw(source_file: &PathBuf) -> Option<SpriteData> {
+// As get_png(), but RGB and RGBA images are quantized to at most `max_colors` colors
+// with median cut instead of taking the red channel. Other color types load as usual.
+pub fn get_png_quantized(source_file: &PathBuf, max_colors: usize) -> Result<SpriteData, String> {
+	let file: File;
+	match File::open(&source_file) {
+		Ok(value) => file = value,
+		_ => {
+			log_error!("sprite_get::get_png_quantized() error: PNG file open error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not open file"));
+		},
+	}
+	
+	let mut decoder = png::Decoder::new(file);
+	decoder.set_transformations(png::Transformations::STRIP_16);
+	
+	let mut reader;
+	match decoder.read_info() {
+		Ok(value) => reader = value,
+		_ => {
+			log_error!("sprite_get::get_png_quantized() error: PNG header read error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not read PNG header"));
+		},
+	}
+	
+	let channels: usize;
+	match reader.info().color_type {
+		png::ColorType::Rgb => channels = 3,
+		png::ColorType::Rgba => channels = 4,
+		_ => return get_png(source_file),
+	}
+	
+	let mut buffer = vec![0; reader.output_buffer_size()];
+	let frame: png::OutputInfo;
+	match reader.next_frame(&mut buffer) {
+		Ok(value) => frame = value,
+		_ => {
+			log_error!("sprite_get::get_png_quantized() error: PNG data read error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not decode PNG data"));
+		},
+	}
+	
+	let mut rgba_pixels: Vec<u8> = Vec::with_capacity(frame.width as usize * frame.height as usize * 4);
+	
+	for row in buffer[..frame.buffer_size()].chunks(frame.line_size) {
+		for color in row[..frame.width as usize * channels].chunks(channels) {
+			rgba_pixels.extend_from_slice(&color[0..3]);
+			rgba_pixels.push(if channels == 4 { color[3] } else { 0xFF });
+		}
+	}
+	
+	let (pixels, palette): (Vec<u8>, Vec<u8>) = sprite_transform::median_cut(&rgba_pixels, max_colors);
+	
+	log_info!("Note: PNG is truecolor, quantized to {} colors", palette.len() / 4);
+	log_info!("\tFile: {}", &source_file.display());
+	
+	return Ok(
+		SpriteData {
+			width: frame.width as u16,
+			height: frame.height as u16,
+			bit_depth: 8,
+			pixels,
+			palette,
+		}
+	);
+}
+
+
+pub fn get_raw(source_file: &PathBuf) -> Result<SpriteData, String> {
 	// Find if the RAW file has specified its dimensions
 	let mut width: u16 = 0;
 	let mut height: u16 = 0;
@@ -180,20 +286,20 @@ pub fn get_raw(source_file: &PathBuf) -> Option<SpriteData> {
 	}
 	
 	if width == 0 {
-		println!("Warning: will not process RAW as its width was not specified");
-		println!("\tSkipped: {}", &source_file.display());
-		return None;
+		log_warn!("Warning: will not process RAW as its width was not specified");
+		log_warn!("\tSkipped: {}", &source_file.display());
+		return Err(String::from("RAW width not given in file name"));
 	}
 	
 	if height == 0 {
-		println!("Warning: will not process RAW as its height was not specified");
-		println!("\tSkipped: {}", &source_file.display());
-		return None;
+		log_warn!("Warning: will not process RAW as its height was not specified");
+		log_warn!("\tSkipped: {}", &source_file.display());
+		return Err(String::from("RAW height not given in file name"));
 	}
 
 	// All good, return raw data
 	match fs::read(source_file) {
-		Ok(data) => return Some(
+		Ok(data) => return Ok(
 			SpriteData {
 				width: width,
 				height: height,
@@ -204,41 +310,59 @@ pub fn get_raw(source_file: &PathBuf) -> Option<SpriteData> {
 		),
 		
 		_ => {
-			println!("sprite_get::get_raw() error: RAW file read error");
-			println!("\tSkipped: {}", &source_file.display());
-			return None;
+			log_error!("sprite_get::get_raw() error: RAW file read error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not read file"));
 		},
 	}
 }
 
 
-pub fn get_bin(source_file: &PathBuf) -> Option<SpriteData> {
+pub fn get_bin(source_file: &PathBuf) -> Result<SpriteData, String> {
 	match fs::read(source_file) {
-		Ok(bin_data) => return get_bin_data(&bin_data),
+		Ok(bin_data) => return get_bin_data_from(&bin_data, &source_file.display().to_string()),
 		_ => {
-			println!("sprite_get::get_bin() error: BIN file read error");
-			println!("\tSkipped: {}", &source_file.display());
-			return None;
+			log_error!("sprite_get::get_bin() error: BIN file read error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not read file"));
 		},
 	}
 }
 
 
-pub fn get_bin_data(bin_data: &Vec<u8>) -> Option<SpriteData> {
+pub fn get_bin_data(bin_data: &Vec<u8>) -> Result<SpriteData, String> {
+	return get_bin_data_from(bin_data, "<memory>");
+}
+
+
+// As get_bin_data(), with `source` naming the data's origin in warnings.
+pub fn get_bin_data_from(bin_data: &Vec<u8>, source: &str) -> Result<SpriteData, String> {
 	if bin_data.len() < 0x20 {
-		println!("Input .BIN file has less than 32 bytes, skipping.");
-		return None;
+		log_warn!("Input .BIN file has less than 32 bytes, skipping.");
+		return Err(String::from("File is truncated (under 32 bytes)"));
 	}
 	
 	if bin_data[0x00] > 0x01 {
-		println!("Input .BIN file not a sprite, skipping.");
-		return None;
+		log_warn!("Input .BIN file not a sprite, skipping.");
+		return Err(String::from("File is not a sprite"));
 	}
 	
 	let header: BinHeader = bin_sprite::get_header(bin_data[0x0..0x10].to_vec());
 	
 	if header.compressed {
-		return Some(sprite_compress::decompress(bin_data, header));
+		let mut sprite_data: SpriteData;
+		
+		match sprite_compress::decompress(bin_data, header) {
+			Some(value) => sprite_data = value,
+			None => {
+				log_warn!("sprite_get::get_bin_data() warning: Compressed sprite data is corrupt, skipping.");
+				log_warn!("\tSource: {}", source);
+				return Err(String::from("Compressed sprite data is corrupt"));
+			},
+		}
+		
+		pad_short_pixels(&mut sprite_data, source);
+		return Ok(sprite_data);
 	}
 	
 	else {
@@ -249,9 +373,21 @@ pub fn get_bin_data(bin_data: &Vec<u8>) -> Option<SpriteData> {
 		
 		if header.clut == 0x20 {
 			// Get embedded palette
-			let color_count: usize = 2usize.pow(header.bit_depth as u32);
-			palette = vec![0; color_count * 4];
-			palette.copy_from_slice(&bin_data[0x10..0x10 + color_count * 4]);
+			let color_count: usize;
+			
+			match bin_sprite::embedded_color_count(bin_data, &header) {
+				Some(value) => color_count = value,
+				None => {
+					log_warn!("sprite_get::get_bin_data() warning: Embedded palette is truncated, skipping.");
+					log_warn!("\tSource: {}", source);
+					return Err(String::from("Embedded palette is truncated"));
+				},
+			}
+			
+			palette = bin_data[0x10..0x10 + color_count * 4].to_vec();
+			
+			// Short palettes are padded out to the full bit depth
+			palette.resize(4 * 2usize.pow(header.bit_depth as u32), 0x00);
 			
 			// Move pointer past palette
 			pointer = bin_sprite::HEADER_SIZE + (color_count * 4) as usize;
@@ -261,27 +397,55 @@ pub fn get_bin_data(bin_data: &Vec<u8>) -> Option<SpriteData> {
 			pointer = bin_sprite::HEADER_SIZE;
 		}
 		
-		let mut pixels: Vec<u8> = vec![0; bin_data.len() - pointer];
-		pixels.copy_from_slice(&bin_data[pointer..]);
+		// The palette check above keeps the pointer within the data
+		let mut pixels: Vec<u8> = bin_data[pointer..].to_vec();
 		
 		if header.bit_depth == 4 {
 			pixels = sprite_transform::bpp_from_4(pixels, true);
 		}
 		
-		return Some(
-			SpriteData {
-				width: header.width,
-				height: header.height,
-				bit_depth: header.bit_depth,
-				pixels,
-				palette,
-			}
-		);
+		// Drop alignment padding
+		let pixel_count: usize = header.width as usize * header.height as usize;
+		
+		if pixels.len() > pixel_count {
+			pixels.truncate(pixel_count);
+		}
+		
+		let mut sprite_data: SpriteData = SpriteData {
+			width: header.width,
+			height: header.height,
+			bit_depth: header.bit_depth,
+			pixels,
+			palette,
+		};
+		
+		pad_short_pixels(&mut sprite_data, source);
+		return Ok(sprite_data);
 	}
 }
 
 
-pub fn get_bmp(source_file: &PathBuf) -> Option<SpriteData> {
+// Pads sprites with fewer pixels than their header declares, warning about it.
+// Returns true if the sprite was short.
+fn pad_short_pixels(sprite_data: &mut SpriteData, source: &str) -> bool {
+	let pixel_count: usize = sprite_data.width as usize * sprite_data.height as usize;
+	
+	if sprite_data.pixels.len() >= pixel_count {
+		return false;
+	}
+	
+	log_warn!("sprite_get::get_bin_data() warning: Sprite data is truncated");
+	log_warn!("\tDeclared {}x{} ({} pixels), found {} pixels",
+		sprite_data.width, sprite_data.height, pixel_count, sprite_data.pixels.len()
+	);
+	log_warn!("\tSource: {}", source);
+	
+	sprite_data.pixels.resize(pixel_count, 0u8);
+	return true;
+}
+
+
+pub fn get_bmp(source_file: &PathBuf) -> Result<SpriteData, String> {
 	// Not using BMP::new_from_file as it does not account for
 	// failing to read from a file and will panic if it does
 	
@@ -290,9 +454,9 @@ pub fn get_bmp(source_file: &PathBuf) -> Option<SpriteData> {
 	match fs::read(source_file) {
 		Ok(value) => bytes = value,
 		_ => {
-			println!("sprite_get::get_bmp() error: BMP file read error");
-			println!("\tSkipped: {}", &source_file.display());
-			return None;
+			log_error!("sprite_get::get_bmp() error: BMP file read error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not read file"));
 		},
 	}
 	
@@ -306,9 +470,9 @@ pub fn get_bmp(source_file: &PathBuf) -> Option<SpriteData> {
 	match BMP::get_dib_header(&bmp) {
 		Ok(header) => dib_header = header,
 		_ => {
-			println!("sprite_get::get_bmp() error: Could not read DIB header");
-			println!("\tSkipped: {}", &source_file.display());
-			return None;
+			log_error!("sprite_get::get_bmp() error: Could not read DIB header");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not read BMP header"));
 		},
 	}
 	
@@ -322,8 +486,19 @@ pub fn get_bmp(source_file: &PathBuf) -> Option<SpriteData> {
 	
 	let start: usize = file_header.bfOffBits as usize;
 	
-	let mut pixel_array: Vec<u8> = vec![0; pixel_array_len];
-	pixel_array.copy_from_slice(&bmp.contents[start..start + pixel_array_len]);
+	if width == 0 || height == 0 {
+		log_error!("sprite_get::get_bmp() error: image has no pixels");
+		log_error!("\tSkipped: {}", &source_file.display());
+		return Err(String::from("Image is empty"));
+	}
+	
+	if start + pixel_array_len > bmp.contents.len() {
+		log_error!("sprite_get::get_bmp() error: pixel array runs past the end of the file");
+		log_error!("\tSkipped: {}", &source_file.display());
+		return Err(String::from("File is truncated (BMP pixel array)"));
+	}
+	
+	let mut pixel_array: Vec<u8> = bmp.contents[start..start + pixel_array_len].to_vec();
 	
 	// Bit depth handling
 	match dib_header.bitcount {
@@ -332,9 +507,9 @@ pub fn get_bmp(source_file: &PathBuf) -> Option<SpriteData> {
 		4 => pixel_array = sprite_transform::bpp_from_4(pixel_array, false),
 		8 => (),
 		_ => {
-			println!("Warning: Skipping BMP as its color depth is not supported ({})", dib_header.bitcount);
-			println!("\tSkipped: {}", &source_file.display());
-			return None;
+			log_warn!("Warning: Skipping BMP as its color depth is not supported ({})", dib_header.bitcount);
+			log_warn!("\tSkipped: {}", &source_file.display());
+			return Err(format!("Unsupported BMP color depth ({})", dib_header.bitcount));
 		},
 	}
 	
@@ -350,14 +525,14 @@ pub fn get_bmp(source_file: &PathBuf) -> Option<SpriteData> {
 	
 	// Invalid BMP	
 	if std::cmp::max(width, height) > u16::MAX as usize {
-		println!("sprite_get::get_bmp() error: image dimensions exceed sprite maximum of 65535px per side");
-		println!("\tSkipped: {}", &source_file.display());
-		return None;
+		log_error!("sprite_get::get_bmp() error: image dimensions exceed sprite maximum of 65535px per side");
+		log_error!("\tSkipped: {}", &source_file.display());
+		return Err(String::from("Image exceeds 65535 pixels per side"));
 	}
 	
 	if pixel_vector.len() != width * height {
-		println!("sprite_get::get_bmp() error: bad BMP: pixel count mismatches image dimensions, result may differ");
-		println!("\tFile: {}", &source_file.display());
+		log_error!("sprite_get::get_bmp() error: bad BMP: pixel count mismatches image dimensions, result may differ");
+		log_error!("\tFile: {}", &source_file.display());
 		pixel_vector.resize((dib_header.width * dib_header.height.abs() as u32) as usize, 0u8);
 	}
 	
@@ -394,6 +569,12 @@ pub fn get_bmp(source_file: &PathBuf) -> Option<SpriteData> {
 		None => color_count = 2u16.pow(bit_depth as u32) as usize,
 	}
 	
+	if index + color_size * color_count > bmp.contents.len() {
+		log_error!("sprite_get::get_bmp() error: color table runs past the end of the file");
+		log_error!("\tSkipped: {}", &source_file.display());
+		return Err(String::from("File is truncated (BMP color table)"));
+	}
+	
 	// Create and populate palette
 	let mut palette: Vec<u8> = vec![0; color_count * 4];
 	
@@ -411,7 +592,7 @@ pub fn get_bmp(source_file: &PathBuf) -> Option<SpriteData> {
 		}
 	}
 	
-	return Some(
+	return Ok(
 		SpriteData {
 			width: width as u16,
 			height: height as u16,
@@ -420,4 +601,247 @@ pub fn get_bmp(source_file: &PathBuf) -> Option<SpriteData> {
 			palette,
 		}
 	);
+}
+
+
+pub fn get_tga(source_file: &PathBuf) -> Result<SpriteData, String> {
+	let bytes: Vec<u8>;
+	match fs::read(source_file) {
+		Ok(value) => bytes = value,
+		_ => {
+			log_error!("sprite_get::get_tga() error: TGA file read error");
+			log_error!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Could not read file"));
+		},
+	}
+	
+	if bytes.len() < TGA_HEADER_SIZE {
+		log_error!("sprite_get::get_tga() error: TGA file is too short to contain a header");
+		log_error!("\tSkipped: {}", &source_file.display());
+		return Err(String::from("File is truncated (no TGA header)"));
+	}
+	
+	let id_length: usize = bytes[0] as usize;
+	let color_map_type: u8 = bytes[1];
+	let image_type: u8 = bytes[2];
+	let map_first: usize = u16::from_le_bytes([bytes[3], bytes[4]]) as usize;
+	let map_length: usize = u16::from_le_bytes([bytes[5], bytes[6]]) as usize;
+	let map_entry_size: usize = bytes[7] as usize;
+	let width: usize = u16::from_le_bytes([bytes[12], bytes[13]]) as usize;
+	let height: usize = u16::from_le_bytes([bytes[14], bytes[15]]) as usize;
+	let pixel_depth: u8 = bytes[16];
+	let descriptor: u8 = bytes[17];
+	
+	match image_type {
+		1 | 9 => (),
+		
+		2 | 10 => {
+			log_warn!("Warning: Skipping TGA as it is truecolor, only color-mapped TGAs are supported");
+			log_warn!("\tSkipped: {}", &source_file.display());
+			return Err(String::from("Unsupported color type (truecolor TGA)"));
+		},
+		
+		_ => {
+			log_warn!("Warning: Skipping TGA as its image type is not supported ({})", image_type);
+			log_warn!("\tSkipped: {}", &source_file.display());
+			return Err(format!("Unsupported TGA image type ({})", image_type));
+		},
+	}
+	
+	if color_map_type != 1 || pixel_depth != 8 {
+		log_warn!("Warning: Skipping TGA as it is not an 8-bit color-mapped image");
+		log_warn!("\tSkipped: {}", &source_file.display());
+		return Err(String::from("Unsupported color type (TGA is not 8-bit color-mapped)"));
+	}
+	
+	// Color map
+	let map_start: usize = TGA_HEADER_SIZE + id_length;
+	let entry_bytes: usize = (map_entry_size + 7) / 8;
+	let map_end: usize = map_start + map_length * entry_bytes;
+	
+	if ![2, 3, 4].contains(&entry_bytes) || bytes.len() < map_end {
+		log_error!("sprite_get::get_tga() error: bad TGA color map");
+		log_error!("\tSkipped: {}", &source_file.display());
+		return Err(String::from("File is truncated (bad TGA color map)"));
+	}
+	
+	// Entries start at map_first, anything before it stays transparent black
+	let mut palette: Vec<u8> = vec![0; (map_first + map_length) * 4];
+	
+	for entry in 0..map_length {
+		let cursor: usize = map_start + entry * entry_bytes;
+		let color: usize = 4 * (map_first + entry);
+		
+		match entry_bytes {
+			// A1 R5 G5 B5
+			2 => {
+				let value: u16 = u16::from_le_bytes([bytes[cursor], bytes[cursor + 1]]);
+				palette[color + 0] = (((value >> 10) & 0x1F) as u8) << 3;
+				palette[color + 1] = (((value >> 5) & 0x1F) as u8) << 3;
+				palette[color + 2] = ((value & 0x1F) as u8) << 3;
+				palette[color + 3] = 0x80;
+			},
+			
+			// BGR(A)
+			_ => {
+				palette[color + 0] = bytes[cursor + 2];
+				palette[color + 1] = bytes[cursor + 1];
+				palette[color + 2] = bytes[cursor + 0];
+				
+				if entry_bytes == 4 {
+					palette[color + 3] = bytes[cursor + 3];
+				} else {
+					palette[color + 3] = 0x80;
+				}
+			},
+		}
+	}
+	
+	// Pixels
+	let pixel_count: usize = width * height;
+	let mut pixel_vector: Vec<u8> = Vec::with_capacity(pixel_count);
+	let mut cursor: usize = map_end;
+	
+	if image_type == 1 {
+		let end: usize = std::cmp::min(cursor + pixel_count, bytes.len());
+		pixel_vector.extend_from_slice(&bytes[cursor..end]);
+	}
+	
+	else {
+		while pixel_vector.len() < pixel_count && cursor < bytes.len() {
+			let packet: u8 = bytes[cursor];
+			let count: usize = (packet & 0x7F) as usize + 1;
+			cursor += 1;
+			
+			// Run-length packet, one index repeated
+			if packet & 0x80 != 0 {
+				if cursor >= bytes.len() {
+					break;
+				}
+				
+				pixel_vector.extend(std::iter::repeat(bytes[cursor]).take(count));
+				cursor += 1;
+			}
+			
+			// Raw packet
+			else {
+				let end: usize = std::cmp::min(cursor + count, bytes.len());
+				pixel_vector.extend_from_slice(&bytes[cursor..end]);
+				cursor = end;
+			}
+		}
+		
+		pixel_vector.truncate(pixel_count);
+	}
+	
+	if pixel_vector.len() != pixel_count {
+		log_error!("sprite_get::get_tga() error: bad TGA: pixel count mismatches image dimensions, result may differ");
+		log_error!("\tFile: {}", &source_file.display());
+		pixel_vector.resize(pixel_count, 0u8);
+	}
+	
+	// Origin, TGAs default to bottom-left
+	if descriptor & 0x20 == 0 {
+		pixel_vector = sprite_transform::flip_v(pixel_vector, width, height);
+	}
+	
+	if descriptor & 0x10 != 0 {
+		pixel_vector = sprite_transform::flip_h(pixel_vector, width, height);
+	}
+	
+	return Ok(
+		SpriteData {
+			width: width as u16,
+			height: height as u16,
+			bit_depth: 8,
+			pixels: pixel_vector,
+			palette,
+		}
+	);
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	#[test]
+	fn compressed_and_uncompressed_4bpp_match() {
+		let width: u16 = 8;
+		let height: u16 = 8;
+		let mut pixels: Vec<u8> = Vec::new();
+		
+		// Distinct neighbours, so a swapped nibble order can't go unnoticed
+		for pixel in 0..width as usize * height as usize {
+			pixels.push(((pixel * 7 + pixel / 3) % 16) as u8);
+		}
+		
+		let compressed_data: sprite_compress::CompressedData = sprite_compress::compress(SpriteData {
+			width,
+			height,
+			bit_depth: 4,
+			pixels: pixels.clone(),
+			palette: Vec::new(),
+		});
+		
+		let mut compressed: Vec<u8> = bin_sprite::make_header(true, 0, 4, width, height, 0, 0, 0);
+		compressed.extend(sprite_compress::compressed_to_bin(&compressed_data));
+		
+		let mut uncompressed: Vec<u8> = bin_sprite::make_header(false, 0, 4, width, height, 0, 0, 0);
+		uncompressed.extend(sprite_transform::bpp_to_4(pixels.clone(), true));
+		
+		let from_compressed: SpriteData = get_bin_data(&compressed).unwrap();
+		let from_uncompressed: SpriteData = get_bin_data(&uncompressed).unwrap();
+		
+		assert_eq!(from_compressed.pixels, from_uncompressed.pixels);
+		assert_eq!(from_uncompressed.pixels, pixels);
+	}
+	
+	
+	#[test]
+	fn short_pixels_are_padded() {
+		let mut short: SpriteData = SpriteData {
+			width: 4,
+			height: 4,
+			bit_depth: 8,
+			pixels: vec![1; 10],
+			palette: Vec::new(),
+		};
+		
+		assert!(pad_short_pixels(&mut short, "test"));
+		assert_eq!(short.pixels.len(), 16);
+		
+		let mut full: SpriteData = SpriteData {
+			width: 4,
+			height: 4,
+			bit_depth: 8,
+			pixels: vec![1; 16],
+			palette: Vec::new(),
+		};
+		
+		assert!(!pad_short_pixels(&mut full, "test"));
+		assert_eq!(full.pixels.len(), 16);
+	}
+	
+	
+	#[test]
+	fn short_8bpp_palette_is_padded() {
+		// 8bpp 4x4 sprite with room for only 16 embedded colors
+		let mut bin_data: Vec<u8> = bin_sprite::make_header(false, 0x20, 8, 4, 4, 0, 0, 0);
+		
+		for color in 0..16u8 {
+			bin_data.extend_from_slice(&[color, color, color, 0x80]);
+		}
+		
+		let pixels: Vec<u8> = (0..16).collect();
+		bin_data.extend(&pixels);
+		
+		let sprite_data: SpriteData = get_bin_data_from(&bin_data, "test").unwrap();
+		
+		assert_eq!(sprite_data.pixels, pixels);
+		assert_eq!(sprite_data.palette.len(), 256 * 4);
+		assert_eq!(&sprite_data.palette[4 * 15..4 * 16], &[15, 15, 15, 0x80]);
+		assert!(sprite_data.palette[4 * 16..].iter().all(|byte| *byte == 0x00));
+	}
 }
\ No newline at end of file
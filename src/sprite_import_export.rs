@@ -4,6 +4,7 @@ use std::fs;
 use std::fs::File;
 use std::path::PathBuf;
 use std::ops::Deref;
+use serde::Serialize;
 
 use godot::prelude::*;
 use godot::classes::Image;
@@ -13,16 +14,42 @@ use crate::bin_sprite;
 use crate::sprite_get;
 use crate::sprite_compress;
 use crate::sprite_transform;
+use crate::convert;
+use crate::bin_cell::Cell;
+use crate::logging::*;
 
 use bin_sprite::BinSprite;
 use sprite_compress::SpriteData;
 
 
+#[derive(Serialize, Debug)]
+struct ManifestEntry {
+	file: String,
+	width: u16,
+	height: u16,
+	bit_depth: u16,
+	embedded_palette: bool,
+}
+
+
+#[derive(Serialize, Debug)]
+struct AtlasEntry {
+	name: String,
+	x: u32,
+	y: u32,
+	w: u32,
+	h: u32,
+}
+
+
 #[derive(GodotClass)]
 #[class(tool, base=Resource)]
 /// Rust GGXXAC+R sprite importer, based on Ghoul.
 struct SpriteImporter {
 	base: Base<Resource>,
+	/// One entry per file from the last import_sprites() call, in the format returned
+	/// by import_sprite(). After import_spritesheet(), one entry per tile instead.
+	#[var] import_report: Array<Dictionary>,
 }
 
 
@@ -31,6 +58,7 @@ impl IResource for SpriteImporter{
 	fn init(base: Base<Resource>) -> Self {
 		Self {
 			base,
+			import_report: array![],
 		}
 	}
 }
@@ -42,7 +70,7 @@ impl SpriteImporter {
 	#[signal]
 	fn sprite_imported();
 	
-	/// Imports the given sprites.
+	/// Imports the given sprites. Files that fail to import are skipped, see [member import_report].
 	#[func]
 	fn import_sprites(
 		&mut self,
@@ -54,15 +82,24 @@ impl SpriteImporter {
 		as_rgb: bool,
 		reindex: bool,
 		bit_depth: i64,
+		quantize_truecolor: bool,
+		trim_transparent: bool,
+		keep_png_alpha: bool,
 	) -> Array<Gd<BinSprite>> {
 		let file_vector: Vec<GString> = sprites.to_vec();
 		let mut sprite_vector: Array<Gd<BinSprite>> = array![];
+		self.import_report = array![];
 		
 		for item in file_vector {
-			match Self::import_sprite(
-				item, embed_palette, halve_alpha, flip_h, flip_v, as_rgb, reindex, bit_depth
-			) {
-				Some(bin_sprite) => sprite_vector.push(&bin_sprite),
+			let result: Dictionary = Self::import_sprite(
+				item, embed_palette, halve_alpha, flip_h, flip_v, as_rgb, reindex, bit_depth,
+				quantize_truecolor, trim_transparent, keep_png_alpha
+			);
+			
+			self.import_report.push(&result);
+			
+			match result.get("sprite") {
+				Some(bin_sprite) => sprite_vector.push(&bin_sprite.to::<Gd<BinSprite>>()),
 				None => continue,
 			}
 			
@@ -73,6 +110,52 @@ impl SpriteImporter {
 		return sprite_vector;
 	}
 	
+	/// Imports every supported sprite file under `root`, as import_sprites() would.
+	/// Entries are natural sorted within each directory. With `recursive`, subdirectories
+	/// are imported depth-first at their sorted position, so the order stays stable.
+	#[func]
+	fn import_directory(
+		&mut self,
+		root: GString,
+		recursive: bool,
+		embed_palette: bool,
+		halve_alpha: bool,
+		flip_h: bool,
+		flip_v: bool,
+		as_rgb: bool,
+		reindex: bool,
+		bit_depth: i64,
+		quantize_truecolor: bool,
+		trim_transparent: bool,
+		keep_png_alpha: bool,
+	) -> Array<Gd<BinSprite>> {
+		let path_buf: PathBuf = PathBuf::from(String::from(root));
+		
+		if !path_buf.is_dir() {
+			log_error!("Could not find sprite directory!");
+			log_error!("Provided path: {}", path_buf.display());
+			self.import_report = array![];
+			return array![];
+		}
+		
+		let mut files: Vec<GString> = Vec::new();
+		Self::collect_sprite_files(&path_buf, recursive, &mut files);
+		
+		return self.import_sprites(
+			PackedStringArray::from(files), embed_palette, halve_alpha, flip_h, flip_v, as_rgb,
+			reindex, bit_depth, quantize_truecolor, trim_transparent, keep_png_alpha
+		);
+	}
+	
+	/// Imports a single sprite. Returns {"sprite": BinSprite} on success, or
+	/// {"error": String, "file": String} describing why the file could not be imported.
+	/// With `quantize_truecolor`, RGB(A) PNGs get a median cut palette (16 colors if
+	/// `bit_depth` forces 4bpp, 256 otherwise) instead of using the red channel as indices.
+	/// With `trim_transparent`, transparent borders are cropped away (colors with an alpha
+	/// of 0, or index 0 without a palette). The result then also holds "trim_left" and
+	/// "trim_top", the pixels removed from the final sprite's left and top edges.
+	/// With `keep_png_alpha`, palette colors missing from a PNG's tRNS chunk import as
+	/// opaque (0xFF) rather than 0x80, so an untouched palette exports back unchanged.
 	#[func]
 	fn import_sprite(
 		file_path: GString,
@@ -83,40 +166,289 @@ impl SpriteImporter {
 		as_rgb: bool,
 		reindex: bool,
 		bit_depth: i64,
-	) -> Option<Gd<BinSprite>> {
+		quantize_truecolor: bool,
+		trim_transparent: bool,
+		keep_png_alpha: bool,
+	) -> Dictionary {
 		let file_string: String = String::from(file_path);
-		let file: PathBuf = PathBuf::from(file_string);
+		let file: PathBuf = PathBuf::from(file_string.clone());
 		
 		if !file.exists() {
-			return None;
+			return Self::import_error("File does not exist", &file_string);
+		}
+		
+		let extension: String = file.extension()
+			.map_or(String::new(), |os_str| os_str.to_string_lossy().to_lowercase());
+		
+		if !sprite_get::SPRITE_EXTENSIONS.contains(&extension.as_str()) {
+			return Self::import_error("Unsupported file format", &file_string);
 		}
 		
 		let mut data: SpriteData;
+		let sprite_data: Result<SpriteData, String>;
+		
+		if quantize_truecolor && extension == "png" {
+			let max_colors: usize = if bit_depth == 1 { 16 } else { 256 };
+			sprite_data = sprite_get::get_png_quantized(&file, max_colors);
+		} else if keep_png_alpha && extension == "png" {
+			sprite_data = sprite_get::get_png_opaque(&file);
+		} else {
+			sprite_data = sprite_get::get_sprite_file(&file);
+		}
 		
-		match sprite_get::get_sprite_file(&file) {
-			Some(sprite_data) => data = sprite_data,
-			None => return None,
+		match sprite_data {
+			Ok(value) => data = value,
+			Err(message) => return Self::import_error(&message, &file_string),
 		}
 		
 		if data.width == 0 || data.height == 0 {
-			godot_print!("Skipping file as it is empty");
-			godot_print!("\tFile: {:?}", file);
-			return None;
+			log_warn!("Skipping file as it is empty");
+			log_warn!("\tFile: {:?}", file);
+			return Self::import_error("Image is empty", &file_string);
 		}
 		
 		// Trim padding
 		data.pixels = sprite_transform::trim_padding(data.pixels, data.width as usize, data.height as usize);
 		
+		// Trim transparent borders, measured on the sprite as it will be flipped
+		let mut trim_left: usize = 0;
+		let mut trim_top: usize = 0;
+		
+		if trim_transparent {
+			let transparent: [bool; 256] = sprite_transform::transparency_table(&data.palette);
+			let width: usize = data.width as usize;
+			let height: usize = data.height as usize;
+			
+			match sprite_transform::opaque_bounds(&data.pixels, width, height, &transparent) {
+				Some((left, top, crop_width, crop_height)) => {
+					data.pixels = sprite_transform::crop(&data.pixels, width, left, top, crop_width, crop_height);
+					data.width = crop_width as u16;
+					data.height = crop_height as u16;
+					
+					trim_left = if flip_h { width - left - crop_width } else { left };
+					trim_top = if flip_v { height - top - crop_height } else { top };
+				},
+				
+				None => log_info!("Not trimming {:?} as it is fully transparent", file),
+			}
+		}
+		
+		match Self::process_sprite_data(
+			data, embed_palette, halve_alpha, flip_h, flip_v, as_rgb, reindex, bit_depth
+		) {
+			Ok(bin_sprite) => {
+				let mut result: Dictionary = dict! { "sprite": bin_sprite };
+				
+				if trim_transparent {
+					result.set("trim_left", trim_left as i64);
+					result.set("trim_top", trim_top as i64);
+				}
+				
+				return result;
+			},
+			
+			Err(error) => return Self::import_error(&error, &file_string),
+		}
+	}
+	
+	
+	fn import_error(error: &str, file: &str) -> Dictionary {
+		return dict! {
+			"error": error,
+			"file": file,
+		};
+	}
+	
+	
+	// Appends the supported sprite files in `path_buf` to `files`, natural sorted,
+	// descending into subdirectories in place if `recursive` is set.
+	fn collect_sprite_files(path_buf: &PathBuf, recursive: bool, files: &mut Vec<GString>) {
+		let mut entries: Vec<PathBuf> = Vec::new();
+		
+		match fs::read_dir(path_buf) {
+			Ok(value) => {
+				for entry in value {
+					match entry {
+						Ok(dir_entry) => entries.push(dir_entry.path()),
+						_ => continue,
+					}
+				}
+			},
+			
+			_ => {
+				log_warn!("Could not read directory {}", path_buf.display());
+				return;
+			},
+		}
+		
+		entries.sort_by(|a, b| natord::compare(&a.to_string_lossy(), &b.to_string_lossy()));
+		
+		for entry in entries {
+			if entry.is_dir() {
+				if recursive {
+					Self::collect_sprite_files(&entry, recursive, files);
+				}
+				
+				continue;
+			}
+			
+			let extension: String = entry.extension()
+				.map_or(String::new(), |os_str| os_str.to_string_lossy().to_lowercase());
+			
+			if sprite_get::SPRITE_EXTENSIONS.contains(&extension.as_str()) {
+				files.push(GString::from(entry.to_string_lossy().to_string()));
+			}
+		}
+	}
+	
+	
+	/// Imports a spritesheet as `columns * rows` equally sized sprites, in row-major order.
+	/// Every sprite shares the sheet's palette. If `trim_transparent` is set, each tile is
+	/// cropped down to its non-transparent pixels, judged as in import_sprite(). Fully
+	/// transparent tiles are skipped if `skip_empty` is set, otherwise kept as-is.
+	/// [member import_report] gets an entry per imported tile, as import_sprite() would
+	/// return it plus "column" and "row".
+	#[func]
+	fn import_spritesheet(
+		&mut self,
+		file_path: GString,
+		columns: i64,
+		rows: i64,
+		trim_transparent: bool,
+		skip_empty: bool,
+		embed_palette: bool,
+		halve_alpha: bool,
+		flip_h: bool,
+		flip_v: bool,
+		as_rgb: bool,
+		reindex: bool,
+		bit_depth: i64,
+	) -> Array<Gd<BinSprite>> {
+		let mut sprite_array: Array<Gd<BinSprite>> = array![];
+		let file_string: String = String::from(file_path);
+		let file: PathBuf = PathBuf::from(file_string.clone());
+		self.import_report = array![];
+		
+		if columns < 1 || rows < 1 {
+			log_error!("Spritesheet needs at least one column and one row");
+			return sprite_array;
+		}
+		
+		if !file.exists() {
+			return sprite_array;
+		}
+		
+		let mut sheet: SpriteData;
+		
+		match sprite_get::get_sprite_file(&file) {
+			Ok(sprite_data) => sheet = sprite_data,
+			Err(_) => return sprite_array,
+		}
+		
+		let sheet_width: usize = sheet.width as usize;
+		let sheet_height: usize = sheet.height as usize;
+		let tile_width: usize = sheet_width / columns as usize;
+		let tile_height: usize = sheet_height / rows as usize;
+		
+		if tile_width == 0 || tile_height == 0 {
+			log_error!("Spritesheet is too small for a {}x{} grid", columns, rows);
+			log_error!("\tFile: {:?}", file);
+			return sprite_array;
+		}
+		
+		if sheet_width % columns as usize != 0 || sheet_height % rows as usize != 0 {
+			log_warn!("Spritesheet size is not a multiple of the grid, ignoring leftover pixels");
+			log_warn!("\tFile: {:?}", file);
+		}
+		
+		sheet.pixels = sprite_transform::trim_padding(sheet.pixels, sheet_width, sheet_height);
+		let transparent: [bool; 256] = sprite_transform::transparency_table(&sheet.palette);
+		
+		for row in 0..rows as usize {
+			for column in 0..columns as usize {
+				let mut pixels: Vec<u8> = sprite_transform::crop(
+					&sheet.pixels, sheet_width,
+					column * tile_width, row * tile_height,
+					tile_width, tile_height
+				);
+				let mut width: usize = tile_width;
+				let mut height: usize = tile_height;
+				let mut trim_left: usize = 0;
+				let mut trim_top: usize = 0;
+				
+				if trim_transparent || skip_empty {
+					match sprite_transform::opaque_bounds(&pixels, width, height, &transparent) {
+						Some((left, top, crop_width, crop_height)) => {
+							if trim_transparent {
+								pixels = sprite_transform::crop(&pixels, width, left, top, crop_width, crop_height);
+								width = crop_width;
+								height = crop_height;
+								
+								trim_left = if flip_h { tile_width - left - crop_width } else { left };
+								trim_top = if flip_v { tile_height - top - crop_height } else { top };
+							}
+						},
+						
+						None => {
+							if skip_empty {
+								continue;
+							}
+						},
+					}
+				}
+				
+				let data: SpriteData = SpriteData {
+					width: width as u16,
+					height: height as u16,
+					bit_depth: sheet.bit_depth,
+					pixels: pixels,
+					palette: sheet.palette.clone(),
+				};
+				
+				match Self::process_sprite_data(
+					data, embed_palette, halve_alpha, flip_h, flip_v, as_rgb, reindex, bit_depth
+				) {
+					Ok(bin_sprite) => {
+						let mut result: Dictionary = dict! {
+							"sprite": bin_sprite.clone(),
+							"column": column as i64,
+							"row": row as i64,
+						};
+						
+						if trim_transparent {
+							result.set("trim_left", trim_left as i64);
+							result.set("trim_top", trim_top as i64);
+						}
+						
+						self.import_report.push(&result);
+						sprite_array.push(&bin_sprite);
+					},
+					
+					Err(_) => continue,
+				}
+			}
+		}
+		
+		return sprite_array;
+	}
+	
+	
+	// Applies import options to loaded sprite data and builds the BinSprite.
+	fn process_sprite_data(
+		mut data: SpriteData,
+		embed_palette: bool,
+		halve_alpha: bool,
+		flip_h: bool,
+		flip_v: bool,
+		as_rgb: bool,
+		reindex: bool,
+		bit_depth: i64,
+	) -> Result<Gd<BinSprite>, String> {
 		// As RGB (needs to happen before palette embed)
 		if as_rgb && !data.palette.is_empty() {
 			data.pixels = sprite_transform::indexed_as_rgb(data.pixels, &data.palette);
 		}
 		
-		// Reindex
-		if reindex {
-			data.pixels = sprite_transform::reindex_vector(data.pixels);
-		}
-		
 		// Forced bit depth
 		match bit_depth {
 			1 => {
@@ -127,6 +459,11 @@ impl SpriteImporter {
 			_ => data.bit_depth = std::cmp::max(data.bit_depth, 4),
 		}
 		
+		// Reindex. Only 8bpp sprites use the swizzled index layout, same as make_png()
+		if reindex && data.bit_depth == 8 {
+			data.pixels = sprite_transform::reindex_vector(data.pixels);
+		}
+		
 		if data.bit_depth == 4 {
 			data.pixels = sprite_transform::limit_16_colors(data.pixels);
 		}
@@ -197,10 +534,10 @@ impl SpriteImporter {
 			&PackedByteArray::from(data.pixels.clone())
 		) {
 			Some(gd_image) => image = gd_image,
-			_ => return None,
+			_ => return Err(String::from("Could not create image from sprite data")),
 		}
 		
-		return Some(BinSprite::new_from_data(
+		return Ok(BinSprite::new_from_data(
 			// Pixels
 			PackedByteArray::from(data.pixels),
 			// Image
@@ -369,7 +706,8 @@ impl SpriteExporter {
 		external_palette: Vec<u8>,
 		palette_alpha_mode: u64,
 		palette_override: bool,
-		reindex: bool
+		reindex: bool,
+		full_palette: bool
 	) {
 		let png_file: File;
 		match File::create(&file_path) {
@@ -414,36 +752,57 @@ impl SpriteExporter {
 		
 		// Palette
 		let color_count: usize = 2usize.pow(sprite.bit_depth as u32);
-		
-		let mut trns_chunk: Vec<u8> = Vec::new();
+		let source_palette: Vec<u8>;
 		
 		if sprite.palette.is_empty() || palette_override || !palette_include {
-			let mut rgb_palette: Vec<u8> = Vec::new();
-			
-			for index in 0..color_count {
-				rgb_palette.push(external_palette[4 * index + 0]);
-				rgb_palette.push(external_palette[4 * index + 1]);
-				rgb_palette.push(external_palette[4 * index + 2]);
-				trns_chunk.push(external_palette[4 * index + 3]);
-			}
-			
-			encoder.set_palette(rgb_palette);
+			source_palette = external_palette;
+		} else {
+			source_palette = sprite.palette.to_vec();
 		}
 		
-		else {
-			let pal_vec: Vec<u8> = sprite.palette.to_vec();
-			let mut rgb_palette: Vec<u8> = Vec::new();
-			
-			for index in 0..color_count {
-				rgb_palette.push(pal_vec[4 * index + 0]);
-				rgb_palette.push(pal_vec[4 * index + 1]);
-				rgb_palette.push(pal_vec[4 * index + 2]);
-				trns_chunk.push(pal_vec[4 * index + 3]);
-			}
-			
-			encoder.set_palette(rgb_palette);
+		let (rgb_palette, trns_chunk) = Self::png_palette_chunks(
+			&source_palette, color_count, full_palette, palette_alpha_mode
+		);
+		
+		encoder.set_palette(rgb_palette);
+		
+		if !trns_chunk.is_empty() {
+			encoder.set_trns(trns_chunk);
+		}
+		
+		let mut writer = encoder.write_header().unwrap();
+		writer.write_image_data(&working_pixels).unwrap();
+	}
+	
+	
+	// Builds the PLTE and tRNS chunks for make_png() from an RGBA palette.
+	fn png_palette_chunks(
+		source_palette: &[u8], color_count: usize, full_palette: bool, palette_alpha_mode: u64
+	) -> (Vec<u8>, Vec<u8>) {
+		// Some tools expect 256 entries regardless of pixel depth
+		let palette_count: usize;
+		
+		if full_palette {
+			palette_count = 256;
+		} else {
+			palette_count = color_count;
 		}
 		
+		let mut rgb_palette: Vec<u8> = Vec::new();
+		let mut trns_chunk: Vec<u8> = Vec::new();
+		
+		for index in 0..color_count {
+			rgb_palette.push(source_palette[4 * index + 0]);
+			rgb_palette.push(source_palette[4 * index + 1]);
+			rgb_palette.push(source_palette[4 * index + 2]);
+			trns_chunk.push(source_palette[4 * index + 3]);
+		}
+		
+		rgb_palette.resize(3 * palette_count, 0x00);
+		
+		// Unused entries are transparent
+		trns_chunk.resize(palette_count, 0x00);
+		
 		for index in 0..trns_chunk.len() {
 			match palette_alpha_mode {
 				// AS_IS
@@ -468,10 +827,13 @@ impl SpriteExporter {
 			}
 		}
 		
-		encoder.set_trns(trns_chunk);
+		// Trailing opaque entries are implied, leave them out so PNGs without
+		// a full tRNS chunk come back out the way they went in
+		while trns_chunk.last() == Some(&0xFF) {
+			trns_chunk.pop();
+		}
 		
-		let mut writer = encoder.write_header().unwrap();
-		writer.write_image_data(&working_pixels).unwrap();
+		return (rgb_palette, trns_chunk);
 	}
 	
 	
@@ -628,6 +990,8 @@ impl SpriteExporter {
 	
 	
 	/// Saves sprites in the specified format at the specified path.
+	/// With `full_palette`, PNGs always carry a 256-color palette, padded with
+	/// transparent black, even when the pixel data is 4bpp.
 	#[func]
 	fn export_sprites(
 		g_format: GString,
@@ -638,22 +1002,31 @@ impl SpriteExporter {
 		g_palette: PackedByteArray,
 		palette_alpha_mode: u64,
 		palette_override: bool,
-		reindex: bool
+		reindex: bool,
+		write_manifest: bool,
+		full_palette: bool
 	) {
 		let path_str: String = String::from(g_path);
 		let path_buf: PathBuf = PathBuf::from(path_str);
 		
 		if !path_buf.exists() {
-			godot_print!("Could not find export directory!");
+			log_error!("Could not find export directory!");
 			return Default::default();
 		}
 		
 		let mut name_index: u64 = name_start_index;
+		let mut manifest: Vec<ManifestEntry> = Vec::new();
 		
 		// Loop over sprites
 		for mut sprite in g_sprites {
 			let mut file_path: PathBuf = path_buf.clone();
 			
+			if write_manifest && ["bin", "png", "bmp", "raw"].contains(&g_format.to_string().as_str()) {
+				manifest.push(Self::make_manifest_entry(
+					&g_format.to_string(), name_index, sprite.bind().deref()
+				));
+			}
+			
 			match g_format.to_string().as_str() {
 				"bin" => {
 					file_path.push(format!("sprite_{}.bin", name_index));
@@ -677,7 +1050,8 @@ impl SpriteExporter {
 						g_palette.to_vec(),
 						palette_alpha_mode,
 						palette_override,
-						reindex
+						reindex,
+						full_palette
 					);
 				},
 				
@@ -708,9 +1082,329 @@ impl SpriteExporter {
 			
 			name_index += 1;
 		}
+		
+		if write_manifest {
+			Self::save_manifest(path_buf, manifest);
+		}
+	}
+	
+	
+	/// Packs every sprite into one RGBA atlas.png with shelf packing, alongside an atlas.json
+	/// listing each sprite's rectangle. Sprites use their embedded palette if they have one,
+	/// `g_palette` otherwise, with missing colors filled in as grayscale. Palette alpha is
+	/// doubled to full range. `power_of_two` rounds the atlas size up to powers of two.
+	/// Returns true on success.
+	#[func]
+	fn export_atlas(
+		g_sprites: Array<Gd<BinSprite>>,
+		g_palette: PackedByteArray,
+		g_path: GString,
+		power_of_two: bool
+	) -> bool {
+		let path_buf: PathBuf = PathBuf::from(String::from(g_path));
+		
+		if !path_buf.exists() {
+			log_error!("Could not find export directory!");
+			return false;
+		}
+		
+		// (sprite index, width, height), tallest first
+		let mut rects: Vec<(usize, u32, u32)> = Vec::new();
+		
+		for (index, sprite) in g_sprites.iter_shared().enumerate() {
+			match &sprite.bind().image {
+				Some(image) => rects.push((index, image.get_width() as u32, image.get_height() as u32)),
+				None => continue,
+			}
+		}
+		
+		if rects.is_empty() {
+			log_error!("No sprites to pack into an atlas");
+			return false;
+		}
+		
+		rects.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)));
+		
+		// Aim for a roughly square atlas
+		let area: u64 = rects.iter().map(|rect| rect.1 as u64 * rect.2 as u64).sum();
+		let widest: u32 = rects.iter().map(|rect| rect.1).max().unwrap();
+		let mut atlas_width: u32 = std::cmp::max(widest, (area as f64).sqrt().ceil() as u32);
+		
+		if power_of_two {
+			atlas_width = atlas_width.next_power_of_two();
+		}
+		
+		// Shelf packing
+		let mut entries: Vec<AtlasEntry> = Vec::with_capacity(rects.len());
+		let mut placements: Vec<(usize, u32, u32)> = Vec::with_capacity(rects.len());
+		let mut shelf_x: u32 = 0;
+		let mut shelf_y: u32 = 0;
+		let mut shelf_height: u32 = 0;
+		
+		for (index, width, height) in rects.iter().copied() {
+			if shelf_x + width > atlas_width {
+				shelf_y += shelf_height;
+				shelf_x = 0;
+				shelf_height = 0;
+			}
+			
+			placements.push((index, shelf_x, shelf_y));
+			entries.push(AtlasEntry {
+				name: format!("sprite_{}", index),
+				x: shelf_x,
+				y: shelf_y,
+				w: width,
+				h: height,
+			});
+			
+			shelf_x += width;
+			shelf_height = std::cmp::max(shelf_height, height);
+		}
+		
+		let mut atlas_height: u32 = shelf_y + shelf_height;
+		
+		if power_of_two {
+			atlas_height = atlas_height.next_power_of_two();
+		}
+		
+		// Fill missing colors with grayscale
+		let mut external_palette: Vec<u8> = g_palette.to_vec();
+		
+		for index in external_palette.len() / 4..256 {
+			external_palette.extend_from_slice(&[index as u8, index as u8, index as u8, 0xFF]);
+		}
+		
+		let mut atlas: Vec<u8> = vec![0u8; atlas_width as usize * atlas_height as usize * 4];
+		
+		for (index, x, y) in placements {
+			let sprite: Gd<BinSprite> = g_sprites.at(index);
+			let binding = sprite.bind();
+			let image: &Gd<Image> = binding.image.as_ref().unwrap();
+			let width: usize = image.get_width() as usize;
+			let height: usize = image.get_height() as usize;
+			let pixels: &[u8] = binding.pixels.as_slice();
+			
+			let mut palette: Vec<u8> = binding.palette.to_vec();
+			
+			if palette.is_empty() {
+				palette = external_palette.clone();
+			} else {
+				palette.extend_from_slice(&external_palette[std::cmp::min(palette.len(), 0x400)..]);
+			}
+			
+			for row in 0..height {
+				for column in 0..std::cmp::min(width, pixels.len().saturating_sub(row * width)) {
+					let color: usize = 4 * pixels[row * width + column] as usize;
+					let target: usize = 4 * ((y as usize + row) * atlas_width as usize + x as usize + column);
+					let alpha: u8 = palette[color + 3];
+					
+					atlas[target..target + 3].copy_from_slice(&palette[color..color + 3]);
+					atlas[target + 3] = if alpha >= 0x80 { 0xFF } else { alpha * 2 };
+				}
+			}
+		}
+		
+		// atlas.png
+		let mut png_path: PathBuf = path_buf.clone();
+		png_path.push("atlas.png");
+		
+		match File::create(&png_path) {
+			Ok(file) => {
+				let ref mut buffer = BufWriter::new(file);
+				let mut encoder = png::Encoder::new(buffer, atlas_width, atlas_height);
+				encoder.set_color(png::ColorType::Rgba);
+				encoder.set_depth(png::BitDepth::Eight);
+				
+				match encoder.write_header() {
+					Ok(mut writer) => {
+						if writer.write_image_data(&atlas).is_err() {
+							log_error!("Could not write atlas image!");
+							return false;
+						}
+					},
+					
+					_ => {
+						log_error!("Could not write atlas image!");
+						return false;
+					},
+				}
+			},
+			
+			_ => {
+				log_error!("Could not create atlas image!");
+				return false;
+			},
+		}
+		
+		// atlas.json
+		let mut json_path: PathBuf = path_buf;
+		json_path.push("atlas.json");
+		
+		let json_string: String;
+		match serde_json::to_string_pretty(&entries) {
+			Ok(string) => json_string = string,
+			_ => return false,
+		}
+		
+		match File::create(&json_path) {
+			Ok(file) => {
+				let ref mut buffer = BufWriter::new(file);
+				let _ = buffer.write_all(json_string.as_bytes());
+				let _ = buffer.flush();
+			},
+			
+			_ => {
+				log_error!("Could not create atlas manifest!");
+				return false;
+			},
+		}
+		
+		return true;
+	}
+	
+	
+	fn make_manifest_entry(format: &str, name_index: u64, sprite: &BinSprite) -> ManifestEntry {
+		let image: Gd<Image> = sprite.image.clone().unwrap();
+		let width: u16 = image.get_width() as u16;
+		let height: u16 = image.get_height() as u16;
+		
+		let file: String;
+		
+		// Must match the names used by the make_* functions
+		if format == "raw" {
+			file = format!("sprite_{}-W-{}-H-{}.raw", name_index, width, height);
+		} else {
+			file = format!("sprite_{}.{}", name_index, format);
+		}
+		
+		return ManifestEntry {
+			file,
+			width,
+			height,
+			bit_depth: sprite.bit_depth,
+			embedded_palette: !sprite.palette.is_empty(),
+		};
+	}
+	
+	
+	fn save_manifest(mut path_buf: PathBuf, manifest: Vec<ManifestEntry>) {
+		path_buf.push("manifest.json");
+		
+		let json_string: String;
+		match serde_json::to_string_pretty(&manifest) {
+			Ok(string) => json_string = string,
+			_ => return,
+		}
+		
+		match File::create(&path_buf) {
+			Ok(file) => {
+				let ref mut buffer = BufWriter::new(file);
+				let _ = buffer.write_all(json_string.as_bytes());
+				let _ = buffer.flush();
+			},
+			
+			_ => log_error!("Could not create manifest file!"),
+		}
 	}
 
 
+	/// Converts every sprite .bin in a directory, one at a time, without loading them
+	/// all as BinSprites first. Supports "png", "raw", and "bin" (plain copy).
+	/// Returns how many sprites were exported.
+	#[func]
+	fn export_from_directory(
+		g_source: GString,
+		g_format: GString,
+		g_path: GString,
+		name_start_index: u64,
+		g_palette: PackedByteArray,
+	) -> i64 {
+		let source_buf: PathBuf = PathBuf::from(String::from(g_source));
+		let path_buf: PathBuf = PathBuf::from(String::from(g_path));
+		let format: String = g_format.to_string();
+		
+		if !source_buf.exists() {
+			log_error!("Could not find sprite directory!");
+			return 0;
+		}
+		
+		if !path_buf.exists() {
+			log_error!("Could not find export directory!");
+			return 0;
+		}
+		
+		if !["png", "raw", "bin"].contains(&format.as_str()) {
+			log_error!("sprite_import_export::export_from_directory() error: Unsupported format '{}'", format);
+			return 0;
+		}
+		
+		let mut file_vector: Vec<PathBuf> = Vec::new();
+		
+		match fs::read_dir(&source_buf) {
+			Ok(value) => {
+				for entry in value {
+					match entry {
+						Ok(item) => file_vector.push(item.path()),
+						_ => continue,
+					}
+				}
+			},
+			
+			_ => return 0,
+		}
+		
+		file_vector.retain(|file| match file.extension() {
+			Some(os_str) => os_str.to_ascii_lowercase().to_str() == Some("bin"),
+			None => false,
+		});
+		
+		file_vector.sort_by(|a, b| natord::compare(a.to_str().unwrap(), b.to_str().unwrap()));
+		
+		let palette: Vec<u8> = g_palette.to_vec();
+		let mut name_index: u64 = name_start_index;
+		let mut exported: i64 = 0;
+		
+		for item in file_vector {
+			let mut file_path: PathBuf = path_buf.clone();
+			let success: bool;
+			
+			match format.as_str() {
+				"png" => {
+					file_path.push(format!("sprite_{}.png", name_index));
+					success = convert::convert_bin_to_png(&item, &file_path, &palette);
+				},
+				
+				"raw" => {
+					match sprite_get::get_bin(&item) {
+						Ok(data) => {
+							file_path.push(format!("sprite_{}-W-{}-H-{}.raw", name_index, data.width, data.height));
+							success = fs::write(&file_path, &data.pixels).is_ok();
+						},
+						
+						Err(_) => success = false,
+					}
+				},
+				
+				_ => {
+					file_path.push(format!("sprite_{}.bin", name_index));
+					success = fs::copy(&item, &file_path).is_ok();
+				},
+			}
+			
+			if success {
+				exported += 1;
+			} else {
+				log_error!("sprite_import_export::export_from_directory() error: Could not export sprite");
+				log_error!("\tFile: {:?}", item);
+			}
+			
+			name_index += 1;
+		}
+		
+		return exported;
+	}
+	
+	
 	#[func]
 	fn export_png_direct(
 		path: String, sprite: Gd<BinSprite>, palette: PackedByteArray
@@ -720,6 +1414,373 @@ impl SpriteExporter {
 		let _ = directory.pop();
 		let _ = fs::create_dir_all(&directory);
 		
-		Self::make_png(path_buf, &sprite.bind(), false, palette.to_vec(), 3, true, false);
+		Self::make_png(path_buf, &sprite.bind(), false, palette.to_vec(), 3, true, false, false);
+	}
+	
+	
+	/// Exports every sprite used by `cells` as sprite_#.png, drawn onto a shared canvas at the
+	/// offsets of the first cell that uses it, so the exported frames line up when stacked.
+	/// Sprites reused by later cells at different offsets keep their first placement.
+	/// Colors missing from both the sprite and `g_palette` are exported as grayscale.
+	/// Returns the number of sprites exported.
+	#[func]
+	fn export_positioned(
+		g_path: GString,
+		g_sprites: Array<Gd<BinSprite>>,
+		g_cells: Array<Gd<Cell>>,
+		g_palette: PackedByteArray
+	) -> i64 {
+		let path_buf: PathBuf = PathBuf::from(String::from(g_path));
+		
+		if !path_buf.exists() {
+			log_error!("Could not find export directory!");
+			return 0;
+		}
+		
+		// First placement of each sprite
+		let mut placements: Vec<Option<(i32, i32)>> = vec![None; g_sprites.len()];
+		
+		for cell in g_cells.iter_shared() {
+			let binding = cell.bind();
+			let index: usize = binding.sprite_index as usize;
+			let offset: (i32, i32) = (binding.sprite_x_offset as i32, binding.sprite_y_offset as i32);
+			
+			if index >= placements.len() {
+				continue;
+			}
+			
+			match placements[index] {
+				None => placements[index] = Some(offset),
+				
+				Some(placed) => {
+					if placed != offset {
+						log_info!("Sprite # {} is used at more than one offset, keeping the first", index);
+					}
+				},
+			}
+		}
+		
+		// Shared canvas extents
+		let mut left: i32 = i32::MAX;
+		let mut top: i32 = i32::MAX;
+		let mut right: i32 = i32::MIN;
+		let mut bottom: i32 = i32::MIN;
+		
+		for index in 0..placements.len() {
+			let (x, y): (i32, i32);
+			
+			match placements[index] {
+				Some(offset) => (x, y) = offset,
+				None => continue,
+			}
+			
+			let sprite: Gd<BinSprite> = g_sprites.at(index);
+			
+			match &sprite.bind().image {
+				Some(image) => {
+					left = std::cmp::min(left, x);
+					top = std::cmp::min(top, y);
+					right = std::cmp::max(right, x + image.get_width());
+					bottom = std::cmp::max(bottom, y + image.get_height());
+				},
+				
+				None => placements[index] = None,
+			}
+		}
+		
+		if left >= right || top >= bottom {
+			log_error!("No sprites are used by the given cells");
+			return 0;
+		}
+		
+		let canvas_width: usize = (right - left) as usize;
+		let canvas_height: usize = (bottom - top) as usize;
+		
+		// Fill missing colors with grayscale
+		let mut external_palette: Vec<u8> = g_palette.to_vec();
+		
+		for index in external_palette.len() / 4..256 {
+			external_palette.extend_from_slice(&[index as u8, index as u8, index as u8, 0xFF]);
+		}
+		
+		let mut exported: i64 = 0;
+		
+		for index in 0..placements.len() {
+			let (x, y): (i32, i32);
+			
+			match placements[index] {
+				Some(offset) => (x, y) = offset,
+				None => continue,
+			}
+			
+			let sprite: Gd<BinSprite> = g_sprites.at(index);
+			let binding = sprite.bind();
+			let image: &Gd<Image> = binding.image.as_ref().unwrap();
+			let width: usize = image.get_width() as usize;
+			let height: usize = image.get_height() as usize;
+			let origin_x: usize = (x - left) as usize;
+			let origin_y: usize = (y - top) as usize;
+			
+			let pixels: &[u8] = binding.pixels.as_slice();
+			let mut canvas: Vec<u8> = vec![0u8; canvas_width * canvas_height];
+			
+			// Short pixel data leaves the rest of the sprite blank, as in export_atlas()
+			for row in 0..height {
+				let source: usize = row * width;
+				let target: usize = (origin_y + row) * canvas_width + origin_x;
+				let length: usize = std::cmp::min(width, pixels.len().saturating_sub(source));
+				canvas[target..target + length].copy_from_slice(&pixels[source..source + length]);
+			}
+			
+			let canvas_image: Gd<Image>;
+			
+			match Image::create_from_data(
+				canvas_width as i32, canvas_height as i32, false, Format::L8, &PackedByteArray::from(canvas.clone())
+			) {
+				Some(gd_image) => canvas_image = gd_image,
+				None => continue,
+			}
+			
+			let canvas_sprite: Gd<BinSprite> = BinSprite::new_from_data(
+				PackedByteArray::from(canvas), canvas_image, binding.bit_depth, binding.palette.clone()
+			);
+			
+			let mut file_path: PathBuf = path_buf.clone();
+			file_path.push(format!("sprite_{}.png", index));
+			
+			Self::make_png(
+				file_path, &canvas_sprite.bind(), true, external_palette.clone(), 0, false, false, false
+			);
+			
+			exported += 1;
+		}
+		
+		return exported;
+	}
+	
+	
+	/// Exports `g_cells` as an animated GIF, one frame per cell, each shown for the matching
+	/// entry of `g_durations` in 60fps game frames (missing entries count as 1).
+	/// The canvas covers every cell's sprite so frames don't shift between each other.
+	/// `g_palette` becomes the global color table, padded with grayscale; its first color with
+	/// an alpha of 0 is used as the transparent index.
+	/// Returns true on success.
+	#[func]
+	fn export_animation_gif(
+		g_path: GString,
+		g_sprites: Array<Gd<BinSprite>>,
+		g_cells: Array<Gd<Cell>>,
+		g_durations: PackedInt64Array,
+		g_palette: PackedByteArray
+	) -> bool {
+		let path_buf: PathBuf = PathBuf::from(String::from(g_path));
+		
+		// Cell placements, skipping cells without a usable sprite
+		let mut placements: Vec<Option<(usize, i32, i32)>> = Vec::with_capacity(g_cells.len());
+		let mut left: i32 = i32::MAX;
+		let mut top: i32 = i32::MAX;
+		let mut right: i32 = i32::MIN;
+		let mut bottom: i32 = i32::MIN;
+		
+		for cell in g_cells.iter_shared() {
+			let binding = cell.bind();
+			let index: usize = binding.sprite_index as usize;
+			let x: i32 = binding.sprite_x_offset as i32;
+			let y: i32 = binding.sprite_y_offset as i32;
+			
+			if index >= g_sprites.len() {
+				placements.push(None);
+				continue;
+			}
+			
+			let sprite: Gd<BinSprite> = g_sprites.at(index);
+			
+			match &sprite.bind().image {
+				Some(image) => {
+					left = std::cmp::min(left, x);
+					top = std::cmp::min(top, y);
+					right = std::cmp::max(right, x + image.get_width());
+					bottom = std::cmp::max(bottom, y + image.get_height());
+					placements.push(Some((index, x, y)));
+				},
+				
+				None => placements.push(None),
+			}
+		}
+		
+		if left >= right || top >= bottom {
+			log_error!("No sprites are used by the given cells");
+			return false;
+		}
+		
+		let canvas_width: usize = (right - left) as usize;
+		let canvas_height: usize = (bottom - top) as usize;
+		
+		if std::cmp::max(canvas_width, canvas_height) > u16::MAX as usize {
+			log_error!("Animation canvas exceeds the GIF maximum of 65535px per side");
+			return false;
+		}
+		
+		// Global color table, missing colors filled with grayscale
+		let mut palette: Vec<u8> = g_palette.to_vec();
+		palette.truncate(0x400);
+		
+		for index in palette.len() / 4..256 {
+			palette.extend_from_slice(&[index as u8, index as u8, index as u8, 0xFF]);
+		}
+		
+		let color_table: Vec<u8> = palette.chunks(4).flat_map(|color| color[0..3].to_vec()).collect();
+		let transparent: Option<u8> = palette.chunks(4).position(|color| color[3] == 0).map(|index| index as u8);
+		let background: u8 = transparent.unwrap_or(0);
+		
+		let file: File;
+		
+		match File::create(&path_buf) {
+			Ok(value) => file = value,
+			_ => {
+				log_error!("Could not create GIF file!");
+				return false;
+			},
+		}
+		
+		let mut encoder: gif::Encoder<BufWriter<File>>;
+		
+		match gif::Encoder::new(BufWriter::new(file), canvas_width as u16, canvas_height as u16, &color_table) {
+			Ok(value) => encoder = value,
+			_ => {
+				log_error!("Could not write GIF header!");
+				return false;
+			},
+		}
+		
+		let _ = encoder.set_repeat(gif::Repeat::Infinite);
+		
+		// GIF delays are in hundredths of a second. Convert from the running total
+		// so rounding doesn't drift over long animations.
+		let mut elapsed_frames: i64 = 0;
+		
+		for cell in 0..placements.len() {
+			let duration: i64 = std::cmp::max(g_durations.get(cell).unwrap_or(1), 1);
+			let start: i64 = (elapsed_frames * 100 + 30) / 60;
+			elapsed_frames += duration;
+			let delay: i64 = (elapsed_frames * 100 + 30) / 60 - start;
+			
+			let mut canvas: Vec<u8> = vec![background; canvas_width * canvas_height];
+			
+			match placements[cell] {
+				Some((index, x, y)) => {
+					let sprite: Gd<BinSprite> = g_sprites.at(index);
+					let binding = sprite.bind();
+					let image: &Gd<Image> = binding.image.as_ref().unwrap();
+					let width: usize = image.get_width() as usize;
+					let height: usize = image.get_height() as usize;
+					let origin_x: usize = (x - left) as usize;
+					let origin_y: usize = (y - top) as usize;
+					let pixels: &[u8] = binding.pixels.as_slice();
+					
+					for row in 0..height {
+						for column in 0..width {
+							let pixel: usize = row * width + column;
+							
+							if pixel >= pixels.len() {
+								break;
+							}
+							
+							// Transparent colors all map to the one GIF transparent index
+							let color: u8 = pixels[pixel];
+							
+							if palette[4 * color as usize + 3] == 0 {
+								continue;
+							}
+							
+							canvas[(origin_y + row) * canvas_width + origin_x + column] = color;
+						}
+					}
+				},
+				
+				None => (),
+			}
+			
+			let mut frame: gif::Frame = gif::Frame::from_indexed_pixels(
+				canvas_width as u16, canvas_height as u16, canvas, transparent
+			);
+			
+			frame.delay = std::cmp::min(delay, u16::MAX as i64) as u16;
+			frame.dispose = gif::DisposalMethod::Background;
+			
+			match encoder.write_frame(&frame) {
+				Ok(_) => (),
+				_ => {
+					log_error!("Could not write GIF frame # {}", cell);
+					return false;
+				},
+			}
+		}
+		
+		return true;
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	// Writes a 4bpp indexed PNG with the given chunks to a temporary file
+	fn write_indexed_png(name: &str, plte: &[u8], trns: &[u8]) -> PathBuf {
+		let path: PathBuf = std::env::temp_dir().join(name);
+		let file: File = File::create(&path).unwrap();
+		let mut encoder = png::Encoder::new(BufWriter::new(file), 2, 2);
+		encoder.set_color(png::ColorType::Indexed);
+		encoder.set_depth(png::BitDepth::Four);
+		encoder.set_palette(plte.to_vec());
+		
+		if !trns.is_empty() {
+			encoder.set_trns(trns.to_vec());
+		}
+		
+		let mut writer = encoder.write_header().unwrap();
+		writer.write_image_data(&[0x01, 0x23]).unwrap();
+		return path;
+	}
+	
+	
+	#[test]
+	fn untouched_png_palette_round_trips() {
+		let plte: Vec<u8> = (0..48).map(|byte| (byte * 5) as u8).collect();
+		let trns: Vec<u8> = vec![0x00, 0x40, 0x7F];
+		let path: PathBuf = write_indexed_png("ggpr_bin_palette_round_trip.png", &plte, &trns);
+		
+		let opaque: SpriteData = sprite_get::get_png_opaque(&path).unwrap();
+		let (out_plte, out_trns) = SpriteExporter::png_palette_chunks(&opaque.palette, 16, false, 0);
+		
+		assert_eq!(out_plte, plte);
+		assert_eq!(out_trns, trns);
+		
+		// The default import keeps the game's 0x80 for colors missing from tRNS
+		let default: SpriteData = sprite_get::get_png(&path).unwrap();
+		assert_eq!(default.palette[4 * 3 + 3], 0x80);
+		assert_eq!(default.palette[4 * 15 + 3], 0x80);
+		
+		let _ = fs::remove_file(&path);
+	}
+	
+	
+	#[test]
+	fn full_palette_pads_plte_to_256_entries() {
+		let palette: Vec<u8> = [0x10, 0x20, 0x30, 0x80].repeat(16);
+		
+		let (plte, trns) = SpriteExporter::png_palette_chunks(&palette, 16, false, 0);
+		assert_eq!(plte.len(), 16 * 3);
+		assert_eq!(trns.len(), 16);
+		
+		// Padding entries are black and fully transparent
+		let (plte, trns) = SpriteExporter::png_palette_chunks(&palette, 16, true, 0);
+		assert_eq!(plte.len(), 256 * 3);
+		assert_eq!(&plte[..3], &[0x10, 0x20, 0x30]);
+		assert!(plte[16 * 3..].iter().all(|byte| *byte == 0x00));
+		assert_eq!(trns.len(), 256);
+		assert!(trns[16..].iter().all(|byte| *byte == 0x00));
 	}
 }
\ No newline at end of file
@@ -4,6 +4,7 @@ use std::fs;
 use std::fs::File;
 use std::path::PathBuf;
 
+use rayon::prelude::*;
 use godot::prelude::*;
 use godot::classes::Image;
 use godot::classes::image::Format;
@@ -11,6 +12,7 @@ use godot::classes::image::Format;
 use crate::bin_sprite;
 use crate::sprite_get;
 use crate::sprite_compress;
+use crate::logging::*;
 
 use bin_sprite::BinSprite;
 use sprite_compress::SpriteData;
@@ -44,8 +46,8 @@ impl SpriteLoadSave {
 	
 	pub fn load_sprites_pathbuf(path_buf: PathBuf) -> Array<Gd<BinSprite>> {
 		if !path_buf.exists() {
-			godot_print!("Could not find sprite directory!");
-			godot_print!("Provided path: {}", path_buf.display());
+			log_error!("Could not find sprite directory!");
+			log_error!("Provided path: {}", path_buf.display());
 			return array![];
 		}
 		
@@ -62,10 +64,25 @@ impl SpriteLoadSave {
 		}
 		
 		file_vector.sort_by(|a, b| natord::compare(a.to_str().unwrap(), b.to_str().unwrap()));
+		
+		// Read and decompress in parallel. Godot resources are built afterwards,
+		// on this thread, in the same order as file_vector.
+		let decoded: Vec<Option<(Vec<u8>, SpriteData)>> = file_vector
+			.par_iter()
+			.map(|item| Self::decode_sprite_file(item))
+			.collect();
+		
 		let mut sprite_vector: Array<Gd<BinSprite>> = array![];
 		
-		for item in file_vector {
-			match Self::load_sprite_file(item) {
+		for item in decoded {
+			let (bin_data, sprite_data): (Vec<u8>, SpriteData);
+			
+			match item {
+				Some(value) => (bin_data, sprite_data) = value,
+				None => continue,
+			}
+			
+			match Self::build_sprite(bin_data, sprite_data) {
 				Some(sprite) => sprite_vector.push(&sprite),
 				None => continue,
 			}
@@ -75,7 +92,8 @@ impl SpriteLoadSave {
 	}
 	
 	// Part of the loading function that deals with a sprite stored in the file system.
-	fn load_sprite_file(file: PathBuf) -> Option<Gd<BinSprite>> {
+	// Touches no Godot objects, so it is safe to run off the main thread.
+	fn decode_sprite_file(file: &PathBuf) -> Option<(Vec<u8>, SpriteData)> {
 		match file.extension() {
 			Some(os_str) => {
 				if os_str.to_ascii_lowercase().to_str() != Some("bin") {
@@ -87,7 +105,11 @@ impl SpriteLoadSave {
 		}
 	
 		match fs::read(file) {
-			Ok(data) => return Self::load_sprite_data(&data),
+			Ok(data) => match Self::decode_sprite_data(&data) {
+				Some(sprite_data) => return Some((data, sprite_data)),
+				None => return None,
+			},
+			
 			_ => return None,
 		}
 	}
@@ -95,19 +117,30 @@ impl SpriteLoadSave {
 	
 	// Loads BinSprites from a raw binary data vector.
 	pub fn load_sprite_data(bin_data: &Vec<u8>) -> Option<Gd<BinSprite>> {
-		let sprite_data: SpriteData;
-
-		match sprite_get::get_bin_data(&bin_data) {
+		match Self::decode_sprite_data(bin_data) {
+			Some(sprite_data) => return Self::build_sprite(bin_data.clone(), sprite_data),
 			None => return None,
-			Some(data) => {
+		}
+	}
+	
+	
+	// Decompresses a sprite, rejecting empty ones.
+	fn decode_sprite_data(bin_data: &Vec<u8>) -> Option<SpriteData> {
+		match sprite_get::get_bin_data(&bin_data) {
+			Err(_) => return None,
+			Ok(data) => {
 				if data.width == 0 || data.height == 0 {
 					return None;
 				}
 				
-				sprite_data = data;
+				return Some(data);
 			}
 		}
-
+	}
+	
+	
+	// Builds the BinSprite and its image. Must run on the main thread.
+	fn build_sprite(bin_data: Vec<u8>, sprite_data: SpriteData) -> Option<Gd<BinSprite>> {
 		let sprite_image = Image::create_from_data(
 			// Dimensions
 			sprite_data.width as i32,
@@ -121,12 +154,17 @@ impl SpriteLoadSave {
 		);
 		
 		match sprite_image {
-			Some(image) => return Some(BinSprite::new_from_data(
-				PackedByteArray::from(sprite_data.pixels),
-				image,
-				sprite_data.bit_depth,
-				PackedByteArray::from(sprite_data.palette)
-			)),
+			Some(image) => {
+				let mut sprite: Gd<BinSprite> = BinSprite::new_from_data(
+					PackedByteArray::from(sprite_data.pixels),
+					image,
+					sprite_data.bit_depth,
+					PackedByteArray::from(sprite_data.palette)
+				);
+				
+				sprite.bind_mut().set_source_bin(bin_data);
+				return Some(sprite);
+			},
 			
 			_ => {
 				return None;
@@ -141,7 +179,7 @@ impl SpriteLoadSave {
 		let path_buf: PathBuf = PathBuf::from(target_path);
 		
 		if !path_buf.exists() {
-			godot_print!("Could not find sprite directory!");
+			log_error!("Could not find sprite directory!");
 			return Default::default();
 		}
 		
@@ -158,10 +196,10 @@ impl SpriteLoadSave {
 				Ok(file) => bin_file = file,
 				_ => {
 					sprite_number += 1;
-					godot_print!(
+					log_error!(
 						"sprite_load_save::save_sprites() error: Could not create target file!"
 					);
-					godot_print!("\tFile: '{:?}'", &target_file);
+					log_error!("\tFile: '{:?}'", &target_file);
 					continue;
 				}
 			}
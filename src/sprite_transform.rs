@@ -1,4 +1,5 @@
 use std::cmp;
+use std::collections::HashMap;
 
 
 pub fn reindex_vector(vector: Vec<u8>) -> Vec<u8> {
@@ -41,6 +42,33 @@ pub fn indexed_as_rgb(input_pixels: Vec<u8>, palette: &Vec<u8>) -> Vec<u8> {
 }
 
 
+pub fn indexed_to_rgba(input_pixels: &Vec<u8>, palette: &Vec<u8>, bit_depth: u16) -> Vec<u8> {
+	let mut output_pixels: Vec<u8> = Vec::with_capacity(input_pixels.len() * 4);
+	let color_count: usize = palette.len() / 4;
+	
+	if color_count == 0 {
+		output_pixels.resize(input_pixels.len() * 4, 0x00);
+		return output_pixels;
+	}
+	
+	for pixel in 0..input_pixels.len() {
+		let mut index: usize = input_pixels[pixel] as usize;
+		
+		// 4bpp sprites only ever address the first 16 colors
+		if bit_depth == 4 {
+			index &= 0xF;
+		}
+		
+		// Wrap around short palettes instead of reading past them
+		index %= color_count;
+		
+		output_pixels.extend_from_slice(&palette[4 * index..4 * index + 4]);
+	}
+	
+	return output_pixels;
+}
+
+
 pub fn bpp_from_1(input_pixels: Vec<u8>) -> Vec<u8> {
 	let mut output_pixels: Vec<u8> = Vec::new();
 	
@@ -228,4 +256,213 @@ pub fn flip_v(input_pixels: Vec<u8>, width: usize, height: usize) -> Vec<u8> {
 	}
 	
 	return output_pixels;
+}
+
+// Copies the tile_width x tile_height region starting at (x, y) out of a width-wide pixel vector.
+pub fn crop(
+	input_pixels: &Vec<u8>, width: usize, x: usize, y: usize, tile_width: usize, tile_height: usize
+) -> Vec<u8> {
+	let mut output_pixels: Vec<u8> = Vec::with_capacity(tile_width * tile_height);
+	
+	for row in y..y + tile_height {
+		let pointer: usize = row * width + x;
+		output_pixels.extend_from_slice(&input_pixels[pointer..pointer + tile_width]);
+	}
+	
+	return output_pixels;
+}
+
+
+// Marks the palette indices trim_transparent treats as transparent: colors with an
+// alpha of 0, or index 0 when there is no palette.
+pub fn transparency_table(palette: &[u8]) -> [bool; 256] {
+	let mut transparent: [bool; 256] = [false; 256];
+	
+	if palette.is_empty() {
+		transparent[0] = true;
+	}
+	
+	for (index, color) in palette.chunks_exact(4).take(256).enumerate() {
+		transparent[index] = color[3] == 0;
+	}
+	
+	return transparent;
+}
+
+
+// Finds the smallest rectangle holding every pixel whose index isn't marked transparent.
+// Returns (left, top, width, height), or None if every pixel is transparent.
+pub fn opaque_bounds(
+	input_pixels: &Vec<u8>, width: usize, height: usize, transparent: &[bool; 256]
+) -> Option<(usize, usize, usize, usize)> {
+	let mut left: usize = width;
+	let mut right: usize = 0;
+	let mut top: usize = height;
+	let mut bottom: usize = 0;
+	
+	for y in 0..height {
+		for x in 0..width {
+			if transparent[input_pixels[y * width + x] as usize] {
+				continue;
+			}
+			
+			left = cmp::min(left, x);
+			right = cmp::max(right, x);
+			top = cmp::min(top, y);
+			bottom = cmp::max(bottom, y);
+		}
+	}
+	
+	if left > right || top > bottom {
+		return None;
+	}
+	
+	return Some((left, top, right - left + 1, bottom - top + 1));
+}
+
+
+// Quantizes RGBA pixels down to at most `max_colors` colors with median cut.
+// Index 0 is kept for fully transparent pixels, so up to max_colors - 1 boxes are cut.
+// Returns (indexed pixels, RGBA palette) with opaque colors at the default alpha of 0x80.
+pub fn median_cut(rgba_pixels: &[u8], max_colors: usize) -> (Vec<u8>, Vec<u8>) {
+	// Unique opaque colors and how often they appear
+	let mut counts: HashMap<[u8; 3], usize> = HashMap::new();
+	
+	for color in rgba_pixels.chunks(4) {
+		if color[3] != 0 {
+			*counts.entry([color[0], color[1], color[2]]).or_insert(0) += 1;
+		}
+	}
+	
+	let mut boxes: Vec<Vec<([u8; 3], usize)>> = vec![counts.into_iter().collect()];
+	
+	// Split the box with the widest channel until there are enough
+	while boxes.len() < cmp::min(cmp::max(max_colors, 2), 256) - 1 {
+		let mut widest: Option<(usize, usize, u8)> = None;
+		
+		for (number, color_box) in boxes.iter().enumerate() {
+			if color_box.len() < 2 {
+				continue;
+			}
+			
+			for channel in 0..3 {
+				let low: u8 = color_box.iter().map(|entry| entry.0[channel]).min().unwrap();
+				let high: u8 = color_box.iter().map(|entry| entry.0[channel]).max().unwrap();
+				
+				if widest.map_or(true, |(_, _, range)| high - low > range) {
+					widest = Some((number, channel, high - low));
+				}
+			}
+		}
+		
+		let (number, channel): (usize, usize);
+		
+		match widest {
+			Some((box_number, box_channel, _)) => (number, channel) = (box_number, box_channel),
+			None => break,
+		}
+		
+		// Split at the pixel-weighted median
+		let mut color_box: Vec<([u8; 3], usize)> = boxes.swap_remove(number);
+		color_box.sort_by_key(|entry| entry.0[channel]);
+		
+		let total: usize = color_box.iter().map(|entry| entry.1).sum();
+		let mut running: usize = 0;
+		let mut split: usize = 1;
+		
+		for index in 0..color_box.len() - 1 {
+			running += color_box[index].1;
+			split = index + 1;
+			
+			if running * 2 >= total {
+				break;
+			}
+		}
+		
+		let upper: Vec<([u8; 3], usize)> = color_box.split_off(split);
+		boxes.push(color_box);
+		boxes.push(upper);
+	}
+	
+	// Palette from each box's weighted average
+	let mut palette: Vec<u8> = vec![0x00, 0x00, 0x00, 0x00];
+	let mut index_map: HashMap<[u8; 3], u8> = HashMap::new();
+	
+	for color_box in boxes.iter() {
+		if color_box.is_empty() {
+			continue;
+		}
+		
+		let index: u8 = (palette.len() / 4) as u8;
+		let total: usize = color_box.iter().map(|entry| entry.1).sum();
+		
+		for channel in 0..3 {
+			let sum: usize = color_box.iter().map(|entry| entry.0[channel] as usize * entry.1).sum();
+			palette.push(((sum + total / 2) / total) as u8);
+		}
+		
+		palette.push(0x80);
+		
+		for entry in color_box {
+			index_map.insert(entry.0, index);
+		}
+	}
+	
+	let pixels: Vec<u8> = rgba_pixels.chunks(4).map(|color| {
+		if color[3] == 0 {
+			return 0;
+		}
+		
+		return index_map[&[color[0], color[1], color[2]]];
+	}).collect();
+	
+	return (pixels, palette);
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	#[test]
+	fn high_4bpp_indices_wrap_into_palette() {
+		let mut palette: Vec<u8> = Vec::new();
+		
+		for index in 0..16u8 {
+			palette.extend_from_slice(&[index, index, index, 0xFF]);
+		}
+		
+		let pixels: Vec<u8> = vec![0x03, 0x13, 0xFF, 0x20];
+		let rgba: Vec<u8> = indexed_to_rgba(&pixels, &palette, 4);
+		
+		assert_eq!(rgba, vec![
+			0x03, 0x03, 0x03, 0xFF,
+			0x03, 0x03, 0x03, 0xFF,
+			0x0F, 0x0F, 0x0F, 0xFF,
+			0x00, 0x00, 0x00, 0xFF,
+		]);
+	}
+	
+	
+	#[test]
+	fn transparency_follows_palette_alpha() {
+		let palette: Vec<u8> = vec![
+			0x00, 0x00, 0x00, 0x80,
+			0x10, 0x10, 0x10, 0x00,
+		];
+		let transparent: [bool; 256] = transparency_table(&palette);
+		
+		assert!(!transparent[0]);
+		assert!(transparent[1]);
+		assert!(transparency_table(&[])[0]);
+		
+		// Only index 1 is transparent, so index 0 counts towards the bounds
+		let pixels: Vec<u8> = vec![
+			1, 1, 1,
+			1, 0, 1,
+			1, 1, 1,
+		];
+		assert_eq!(opaque_bounds(&pixels, 3, 3, &transparent), Some((1, 1, 1, 1)));
+	}
 }
\ No newline at end of file